@@ -1,46 +1,256 @@
 // Import from `core` instead of from `std` since we are in no-std mode
 use core::result::Result;
 
-// Import heap related library from `alloc`
-// https://doc.rust-lang.org/alloc/index.html
-use alloc::{vec, vec::Vec};
-
 // Import CKB syscalls and structures
 // https://docs.rs/ckb-std/
 use ckb_std::{
-    debug,
     ckb_constants::Source,
-    high_level::{load_script, load_tx_hash, load_cell_data},
-    ckb_types::{bytes::Bytes},
-    high_level::{load_cell, QueryIter},
+    error::SysError,
+    high_level::{load_script, load_script_hash},
+    ckb_types::prelude::*,
+    high_level::{
+        load_cell_lock, load_cell_lock_hash, load_cell_type, load_cell_type_hash, load_input,
+        load_input_since, load_witness_args, QueryIter,
+    },
+    syscalls,
 };
 
 use crate::error::Error;
-use core::str;
-use serde::{Deserialize, Serialize};
+use crate::loader;
+use crate::log::{trace, Cell};
 
-extern crate alloc;
-use alloc::string::{String, ToString};
+use types::address::ckb;
+use types::cell_data::{strip_prefix, DAPP_DATA_PREFIX_LEN};
+use types::config::{self, parse_args, Config, TYPE_ID_LEN};
 use types::kuai_mvp_data::KuaiMvpView;
+use types::operation::Operation;
+use types::record_stream::CkbAddress;
+use types::transition::{self, Transition};
 
 pub fn main() -> Result<(), Error> {
-    let datas = QueryIter::new(load_cell_data, Source::GroupOutput).collect::<Vec<Vec<u8>>>();
-    debug!("{:?}", datas);
+    // records are loaded one at a time, see `loader`
+    let input_count = loader::count(Source::GroupInput)?;
+    let output_count = loader::count(Source::GroupOutput)?;
+    trace(Cell::Tx, "type_script", verify_type_script_group(output_count))?;
+
+    let script = load_script()?;
+    let args = parse_args(&script.args().raw_data()).map_err(Error::from);
+    let (config, type_id) = trace(Cell::Tx, "config", args)?;
+
+    if let Some(type_id) = type_id {
+        let result = verify_type_id(&type_id, input_count, output_count);
+        trace(Cell::Tx, "type_id", result)?;
+    }
+
+    let transition = match (input_count == 0, output_count == 0) {
+        (true, _) => Transition::Create,
+        (false, true) => Transition::Destroy,
+        (false, false) => Transition::Update,
+    };
+
+    match transition {
+        Transition::Create => verify_create(&config, output_count),
+        Transition::Update => verify_update(&config, input_count, output_count),
+        Transition::Destroy => verify_destroy(&config, input_count),
+    }
+}
+
+/// The contract is deployed as a type script and never unlocks a cell. A type script group
+/// holds the inputs and the outputs carrying the script as their type, a lock script group
+/// only the inputs carrying it as their lock
+fn verify_type_script_group(output_count: usize) -> Result<(), Error> {
+    let script_hash = load_script_hash()?;
+    let typed_inputs = QueryIter::new(load_cell_type_hash, Source::GroupInput)
+        .all(|type_hash| type_hash == Some(script_hash));
+    let typed_outputs = QueryIter::new(load_cell_type_hash, Source::Output)
+        .filter(|type_hash| *type_hash == Some(script_hash))
+        .count();
+    if !typed_inputs || typed_outputs != output_count {
+        return Err(Error::NotTypeScript);
+    }
+
+    Ok(())
+}
+
+/// A record of a type id deployment is the only cell of its script group. Its identifier is
+/// derived from the first input of the transaction creating it, the same way as a type id cell
+fn verify_type_id(
+    type_id: &[u8; TYPE_ID_LEN],
+    input_count: usize,
+    output_count: usize,
+) -> Result<(), Error> {
+    if input_count > 1 || output_count > 1 {
+        return Err(Error::DuplicateRecord);
+    }
+    // the identifier was checked when the record was created
+    if input_count == 1 {
+        return Ok(());
+    }
+
+    let script_hash = load_script_hash()?;
+    let output_index = QueryIter::new(load_cell_type_hash, Source::Output)
+        .position(|type_hash| type_hash == Some(script_hash))
+        .ok_or(Error::ItemMissing)?;
+    let first_input = load_input(0, Source::Input)?;
+
+    if &config::type_id(first_input.as_slice(), output_index as u64) != type_id {
+        return Err(Error::TypeIdMismatch);
+    }
+
+    Ok(())
+}
+
+/// Records written by the transaction are held to the limits of the deployment as they are
+/// streamed by `loader::read_record`, and only copied into a view once they pass
+fn load_output_view(config: &Config, index: usize) -> Result<KuaiMvpView, Error> {
+    let mut view = KuaiMvpView::default();
+    loader::read_record(config, true, index, Source::GroupOutput, Some(&mut view))?;
+    Ok(view)
+}
+
+/// Records already on chain are only read, so they can still be updated or destroyed after
+/// the limits are tightened. Only their size is bounded, before anything is loaded
+fn read_input(config: &Config, index: usize, view: Option<&mut KuaiMvpView>) -> Result<(), Error> {
+    let config = Config {
+        max_data_size: config.max_data_size,
+        ..Config::default()
+    };
+    loader::read_record(&config, false, index, Source::GroupInput, view)?;
+    Ok(())
+}
+
+fn load_input_view(config: &Config, index: usize) -> Result<KuaiMvpView, Error> {
+    let mut view = KuaiMvpView::default();
+    read_input(config, index, Some(&mut view))?;
+    Ok(view)
+}
 
-    for data in datas {
-        if data.is_empty() {
-            return Err(Error::NotMvpData)
+fn verify_create(config: &Config, count: usize) -> Result<(), Error> {
+    for index in 0..count {
+        let cell = Cell::Output(index);
+        // nothing is compared, so the record is never copied into a view
+        let record = loader::read_record(config, true, index, Source::GroupOutput, None);
+        let ckb_address = trace(cell, "record", record)?;
+        let ckb_address = ckb_address.as_ref().map(CkbAddress::as_str);
+        trace(cell, "create", transition::verify_create(ckb_address).map_err(Error::from))?;
+        trace(cell, "ckb_address", verify_ckb_address(ckb_address, index))?;
+    }
+
+    Ok(())
+}
+
+/// Each GroupInput record is paired with the GroupOutput at the same index, or with the only
+/// GroupOutput when `RecordModel.update` merges the records of a lock into one. The lock may
+/// only change when the input declares a transfer
+fn verify_update(config: &Config, input_count: usize, output_count: usize) -> Result<(), Error> {
+    let merged = match output_count {
+        1 if input_count > 1 => {
+            let view = load_output_view(config, 0);
+            Some(trace(Cell::Output(0), "record", view)?)
+        }
+        _ if input_count != output_count => {
+            return trace(Cell::Tx, "record_count", Err(Error::RecordCountMismatch));
         }
+        _ => None,
+    };
 
-        let jsonStr = str::from_utf8(&data[8..data.len()]).map_err(|_| Error::DataParseError)?;
-        let view = KuaiMvpView::as_json_str(jsonStr);
-        if !view.verify() {
-            return Err(Error::CkbAddressEmptyError);
+    for index in 0..input_count {
+        let old_view = trace(Cell::Input(index), "record", load_input_view(config, index))?;
+        let output_index = if merged.is_some() { 0 } else { index };
+        let cell = Cell::Output(output_index);
+        let paired;
+        let view = match &merged {
+            Some(view) => view,
+            None => {
+                paired = trace(cell, "record", load_output_view(config, index))?;
+                &paired
+            }
+        };
+        let operation = trace(Cell::Input(index), "operation", load_operation(index))?;
+
+        let input_lock = load_cell_lock_hash(index, Source::GroupInput)?;
+        let output_lock = load_cell_lock_hash(output_index, Source::GroupOutput)?;
+        if input_lock != output_lock && operation != Operation::Transfer {
+            return trace(cell, "lock", Err(Error::LockChanged));
         }
+
+        let result = transition::verify_update(&old_view, view, operation);
+        trace(cell, "update", result.map_err(Error::from))?;
+        trace(cell, "ckb_address", verify_ckb_address(view.ckb_address(), output_index))?;
     }
 
     Ok(())
 }
 
+/// The owner authorizes the destruction by unlocking the record cell, the lock the "ckb" entry
+/// of the record was held to when it was written
+fn verify_destroy(config: &Config, count: usize) -> Result<(), Error> {
+    for index in 0..count {
+        let cell = Cell::Input(index);
+        // the record is still read so that only records of the dapp are destroyed
+        trace(cell, "record", read_input(config, index, None))?;
+        let since = load_input_since(index, Source::GroupInput)?;
+        let result = transition::verify_destroy(config, since);
+        trace(cell, "destroy", result.map_err(Error::from))?;
+    }
+
+    trace(Cell::Tx, "orphaned_records", verify_no_orphaned_records())
+}
+
+/// A destroyed record must not survive as record data in a cell without the type script.
+/// Records of a type id deployment have their own args, so only the code of the type script
+/// is compared
+fn verify_no_orphaned_records() -> Result<(), Error> {
+    let script = load_script()?;
+    let mut prefix = [0u8; DAPP_DATA_PREFIX_LEN];
+    for index in 0.. {
+        let data_len = match syscalls::load_cell_data(&mut prefix, 0, index, Source::Output) {
+            Ok(len) => len,
+            Err(SysError::LengthNotEnough(len)) => len,
+            Err(SysError::IndexOutOfBound) => break,
+            Err(err) => return Err(err.into()),
+        };
+        if data_len < DAPP_DATA_PREFIX_LEN || strip_prefix(&prefix).is_err() {
+            continue;
+        }
+
+        let is_record = load_cell_type(index, Source::Output)?.map_or(false, |type_script| {
+            type_script.code_hash().as_slice() == script.code_hash().as_slice()
+                && type_script.hash_type().as_slice() == script.hash_type().as_slice()
+        });
+        if !is_record {
+            return Err(Error::OrphanedRecord);
+        }
+    }
+
+    Ok(())
+}
+
+/// The "ckb" entry must be the address of the lock of the record cell itself
+fn verify_ckb_address(address: Option<&str>, index: usize) -> Result<(), Error> {
+    let address = address.ok_or(Error::CkbAddressEmptyError)?;
+    let (_, script) = ckb::parse(address).map_err(|_| Error::InvalidCkbAddress)?;
+
+    let lock = load_cell_lock(index, Source::GroupOutput)?;
+    let hash_type = lock.hash_type().as_slice()[0];
+    if !script.matches(lock.code_hash().as_slice(), hash_type, &lock.args().raw_data()) {
+        return Err(Error::CkbAddressLockMismatch);
+    }
+
+    Ok(())
+}
+
+fn load_operation(index: usize) -> Result<Operation, Error> {
+    let witness_args = match load_witness_args(index, Source::GroupInput) {
+        Ok(witness_args) => witness_args,
+        // no witness or not a WitnessArgs, nothing is declared
+        Err(SysError::IndexOutOfBound) | Err(SysError::Encoding) => return Ok(Operation::Update),
+        Err(err) => return Err(err.into()),
+    };
+    let input_type = witness_args.input_type().to_opt().map(|bytes| bytes.raw_data());
+
+    Operation::from_input_type(input_type.as_deref()).map_err(|_| Error::UnknownOperation)
+}
+
 
 
@@ -1,6 +1,16 @@
 use ckb_std::error::SysError;
+use types::address::ChainAddressError;
+use types::cell_data::CellDataError;
+use types::config::{ConfigError, LimitError};
+use types::dweb::DwebError;
+use types::kuai_mvp_data::ViewError;
+use types::label::LabelError;
+use types::record_reader::MoleculeError;
+use types::record_stream::StreamError;
+use types::transition::TransitionError;
 
 /// Error
+#[derive(Clone, Copy, Debug)]
 #[repr(i8)]
 pub enum Error {
     IndexOutOfBound = 1,
@@ -11,6 +21,41 @@ pub enum Error {
     CkbAddressEmptyError,
     DataParseError,
     NotMvpData,
+    DataTooShort,
+    DataPrefixMismatch,
+    LockChanged,
+    UnknownOperation,
+    InvalidCkbAddress,
+    CkbAddressLockMismatch,
+    RecordCountMismatch,
+    RequiredEntryRemoved,
+    CkbAddressChanged,
+    OrphanedRecord,
+    NotTypeScript,
+    UnknownSysError,
+    InvalidUtf8,
+    InvalidMolecule,
+    MissingDataFormat,
+    UnknownDataFormat,
+    InvalidConfig,
+    TooManyEntries,
+    KeyTooLong,
+    ValueTooLong,
+    DataTooLarge,
+    SectionNotAllowed,
+    DuplicateRecord,
+    TypeIdMismatch,
+    ImmutableRecord,
+    LockInPeriod,
+    DuplicateKey,
+    UnsortedKeys,
+    InvalidEthAddress,
+    InvalidBtcAddress,
+    InvalidDwebEntry,
+    NonCanonicalJson,
+    UnknownLabel,
+    RequiredNotAllowed,
+    VerifiedValueChanged,
 }
 
 impl From<SysError> for Error {
@@ -21,8 +66,111 @@ impl From<SysError> for Error {
             ItemMissing => Self::ItemMissing,
             LengthNotEnough(_) => Self::LengthNotEnough,
             Encoding => Self::Encoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+            Unknown(_) => Self::UnknownSysError,
         }
     }
 }
 
+impl From<CellDataError> for Error {
+    fn from(err: CellDataError) -> Self {
+        match err {
+            CellDataError::TooShort => Self::DataTooShort,
+            CellDataError::PrefixMismatch => Self::DataPrefixMismatch,
+            CellDataError::MissingFormat => Self::MissingDataFormat,
+            CellDataError::UnknownFormat => Self::UnknownDataFormat,
+        }
+    }
+}
+
+impl From<ChainAddressError> for Error {
+    fn from(err: ChainAddressError) -> Self {
+        match err {
+            ChainAddressError::Btc(_) => Self::InvalidBtcAddress,
+            ChainAddressError::Eth(_) => Self::InvalidEthAddress,
+        }
+    }
+}
+
+impl From<DwebError> for Error {
+    fn from(_: DwebError) -> Self {
+        Self::InvalidDwebEntry
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        match err {
+            ConfigError::InvalidArgs => Self::InvalidConfig,
+        }
+    }
+}
+
+impl From<LimitError> for Error {
+    fn from(err: LimitError) -> Self {
+        match err {
+            LimitError::TooManyEntries => Self::TooManyEntries,
+            LimitError::KeyTooLong => Self::KeyTooLong,
+            LimitError::ValueTooLong => Self::ValueTooLong,
+            LimitError::DataTooLarge => Self::DataTooLarge,
+            LimitError::SectionNotAllowed => Self::SectionNotAllowed,
+        }
+    }
+}
+
+impl From<TransitionError> for Error {
+    fn from(err: TransitionError) -> Self {
+        match err {
+            TransitionError::MissingCkbAddress => Self::CkbAddressEmptyError,
+            TransitionError::RequiredEntryRemoved => Self::RequiredEntryRemoved,
+            TransitionError::CkbAddressChanged => Self::CkbAddressChanged,
+            TransitionError::ImmutableRecord => Self::ImmutableRecord,
+            TransitionError::LockInPeriod => Self::LockInPeriod,
+            TransitionError::VerifiedValueChanged => Self::VerifiedValueChanged,
+        }
+    }
+}
+
+impl From<LabelError> for Error {
+    fn from(err: LabelError) -> Self {
+        match err {
+            LabelError::UnknownLabel => Self::UnknownLabel,
+            LabelError::RequiredNotAllowed => Self::RequiredNotAllowed,
+        }
+    }
+}
+
+impl From<MoleculeError> for Error {
+    fn from(err: MoleculeError) -> Self {
+        match err {
+            MoleculeError::InvalidUtf8 { .. } => Self::InvalidUtf8,
+            _ => Self::InvalidMolecule,
+        }
+    }
+}
+
+impl From<ViewError> for Error {
+    fn from(err: ViewError) -> Self {
+        match err {
+            ViewError::InvalidJson => Self::DataParseError,
+            ViewError::InvalidUtf8 => Self::InvalidUtf8,
+            ViewError::InvalidMolecule => Self::InvalidMolecule,
+        }
+    }
+}
+
+impl From<StreamError<Error>> for Error {
+    fn from(err: StreamError<Error>) -> Self {
+        match err {
+            StreamError::Load(err) => err,
+            StreamError::Json(err) => ViewError::from(err).into(),
+            StreamError::NonCanonicalJson => Self::NonCanonicalJson,
+            StreamError::Molecule(err) => err.into(),
+            StreamError::Limit(err) => err.into(),
+            StreamError::DuplicateKey => Self::DuplicateKey,
+            StreamError::UnsortedKeys => Self::UnsortedKeys,
+            StreamError::Address(err) => err.into(),
+            StreamError::Dweb(err) => err.into(),
+            StreamError::Label(err) => err.into(),
+        }
+    }
+}
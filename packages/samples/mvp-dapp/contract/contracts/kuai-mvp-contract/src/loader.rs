@@ -0,0 +1,165 @@
+//! Record cell data read with `syscalls::load_cell_data` in windows of a fixed size.
+//!
+//! The size of a record is checked against the deployment before anything is read. The body
+//! is then read by `record_stream::RecordStream` with a fixed amount of state and never loaded
+//! as a whole, so a record breaking the format or the limits never reaches the heap. Entries
+//! are only copied into a view when the caller asks for one.
+
+extern crate alloc;
+use ckb_std::{ckb_constants::Source, error::SysError, syscalls};
+use core::cmp;
+
+use crate::error::Error;
+use types::cell_data::{split_format, strip_prefix, DataFormat, DAPP_DATA_PREFIX_LEN};
+use types::config::{Config, FLAG_REQUIRE_FORMAT_TAG};
+use types::kuai_mvp_data::KuaiMvpView;
+use types::record_stream::{CkbAddress, Loader, RecordStream};
+#[cfg(feature = "serde-json")]
+use alloc::vec;
+#[cfg(feature = "serde-json")]
+use core::str;
+#[cfg(feature = "serde-json")]
+use types::{
+    canonical_json,
+    config::{FLAG_CANONICAL_JSON, FLAG_SORTED_KEYS},
+};
+
+/// Length of the data of a cell, `None` past the last cell of `source`
+pub fn data_len(index: usize, source: Source) -> Result<Option<usize>, Error> {
+    match syscalls::load_cell_data(&mut [], 0, index, source) {
+        Ok(len) => Ok(Some(len)),
+        Err(SysError::LengthNotEnough(len)) => Ok(Some(len)),
+        Err(SysError::IndexOutOfBound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn count(source: Source) -> Result<usize, Error> {
+    let mut index = 0;
+    while data_len(index, source)?.is_some() {
+        index += 1;
+    }
+    Ok(index)
+}
+
+/// Fill `buf` with the cell data from `offset`
+fn load_window(buf: &mut [u8], offset: usize, index: usize, source: Source) -> Result<(), Error> {
+    let len = match syscalls::load_cell_data(buf, offset, index, source) {
+        Ok(len) => len,
+        Err(SysError::LengthNotEnough(len)) => len,
+        Err(err) => return Err(err.into()),
+    };
+    if len < buf.len() {
+        return Err(Error::LengthNotEnough);
+    }
+    Ok(())
+}
+
+/// Body of the record of a cell, behind the prefix and the format tag
+struct CellRecord {
+    index: usize,
+    source: Source,
+    start: usize,
+}
+
+impl Loader for CellRecord {
+    type Error = Error;
+
+    fn load(&self, buf: &mut [u8], offset: usize) -> Result<(), Error> {
+        load_window(buf, self.start + offset, self.index, self.source)
+    }
+}
+
+/// Format and body of the record behind the prefix
+fn split_record<'a>(config: &Config, data: &'a [u8]) -> Result<(DataFormat, &'a [u8]), Error> {
+    if data.is_empty() {
+        return Err(Error::NotMvpData);
+    }
+
+    let body = strip_prefix(data)?;
+    if config.has_flag(FLAG_REQUIRE_FORMAT_TAG) && body.first() == Some(&b'{') {
+        return Err(Error::MissingDataFormat);
+    }
+
+    Ok(split_format(body)?)
+}
+
+/// Read the record of a cell, copying its entries into `view` when one is given. With `verify`
+/// the record is held to `config` as it is read and its "ckb" address is returned, otherwise
+/// only its size is bounded
+pub fn read_record(
+    config: &Config,
+    verify: bool,
+    index: usize,
+    source: Source,
+    view: Option<&mut KuaiMvpView>,
+) -> Result<Option<CkbAddress>, Error> {
+    let len = data_len(index, source)?.ok_or(Error::IndexOutOfBound)?;
+    config.check_data_size(len)?;
+
+    let mut head = [0u8; DAPP_DATA_PREFIX_LEN + 1];
+    let head_len = cmp::min(len, head.len());
+    load_window(&mut head[..head_len], 0, index, source)?;
+    let (format, rest) = split_record(config, &head[..head_len])?;
+    let start = head_len - rest.len();
+    let rules = if verify { Some(config) } else { None };
+
+    #[cfg(feature = "serde-json")]
+    if format == DataFormat::Json {
+        return read_json(rules, index, source, start, len, view);
+    }
+
+    let mut stream = RecordStream::new(
+        CellRecord {
+            index,
+            source,
+            start,
+        },
+        len - start,
+    );
+    stream.read(format, rules, view)?;
+
+    Ok(stream.ckb_address())
+}
+
+/// The record is loaded and parsed by `serde_json`, then checked as a view, only to compare
+/// cycles
+#[cfg(feature = "serde-json")]
+fn read_json(
+    rules: Option<&Config>,
+    index: usize,
+    source: Source,
+    start: usize,
+    len: usize,
+    view: Option<&mut KuaiMvpView>,
+) -> Result<Option<CkbAddress>, Error> {
+    let mut data = vec![0u8; len - start];
+    load_window(&mut data, start, index, source)?;
+    let json_str = str::from_utf8(&data).map_err(|_| Error::InvalidUtf8)?;
+    let canonical = rules.is_some_and(|config| config.has_flag(FLAG_CANONICAL_JSON));
+    if canonical && !canonical_json::is_canonical(&data) {
+        return Err(Error::NonCanonicalJson);
+    }
+    let record = KuaiMvpView::as_json_str(json_str)?;
+
+    let mut ckb_address = None;
+    if let Some(config) = rules {
+        config.check_view(&record)?;
+        if config.has_flag(FLAG_SORTED_KEYS) {
+            if !record.keys_sorted() {
+                return Err(Error::UnsortedKeys);
+            }
+        } else if record.duplicate_key().is_some() {
+            return Err(Error::DuplicateKey);
+        }
+        record.verify_addresses()?;
+        record.verify_dweb()?;
+        record.verify_labels()?;
+        ckb_address = record.ckb_address().and_then(CkbAddress::new);
+    }
+    if let Some(view) = view {
+        *view = record;
+    }
+
+    Ok(ckb_address)
+}
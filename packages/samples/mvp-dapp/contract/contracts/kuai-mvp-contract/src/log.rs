@@ -0,0 +1,50 @@
+//! Trace of the validation decisions of the script, one line per cell and rule:
+//!
+//! ```text
+//! kuai-mvp: output#0 record ok
+//! kuai-mvp: input#1 update err RequiredEntryRemoved (15)
+//! ```
+//!
+//! Lines are only written with the `debug-log` feature, without it `trace` returns the result
+//! untouched and the formatting is compiled out of the binary.
+
+use crate::error::Error;
+
+#[cfg(feature = "debug-log")]
+extern crate alloc;
+
+/// Cell a rule is checked on, by its index in the script group
+#[derive(Clone, Copy)]
+pub enum Cell {
+    Input(usize),
+    Output(usize),
+    /// Rules on the whole transaction
+    Tx,
+}
+
+/// Pass `result` through, writing it to the debug syscall with the `debug-log` feature
+#[inline(always)]
+pub fn trace<T>(cell: Cell, rule: &str, result: Result<T, Error>) -> Result<T, Error> {
+    #[cfg(feature = "debug-log")]
+    write(cell, rule, &result);
+    #[cfg(not(feature = "debug-log"))]
+    let _ = (cell, rule);
+    result
+}
+
+#[cfg(feature = "debug-log")]
+fn write<T>(cell: Cell, rule: &str, result: &Result<T, Error>) {
+    use alloc::{format, string::String};
+
+    let cell = match cell {
+        Cell::Input(index) => format!("input#{}", index),
+        Cell::Output(index) => format!("output#{}", index),
+        Cell::Tx => String::from("tx"),
+    };
+    let message = match result {
+        Ok(_) => format!("kuai-mvp: {} {} ok", cell, rule),
+        Err(err) => format!("kuai-mvp: {} {} err {:?} ({})", cell, rule, err, *err as i8),
+    };
+    // `ckb_std::debug!` is empty without debug assertions, the feature also traces release builds
+    ckb_std::syscalls::debug(message);
+}
@@ -1,9 +1,10 @@
 use core::slice::SlicePattern;
 use super::*;
+use ckb_testtool::builtin::ALWAYS_SUCCESS;
 use ckb_testtool::context::Context;
 use ckb_testtool::ckb_types::{
     bytes::Bytes,
-    core::TransactionBuilder,
+    core::{TransactionBuilder, TransactionView},
     packed::*,
     prelude::*,
 };
@@ -12,12 +13,52 @@ use ckb_testtool::ckb_hash::blake2b_256;
 use ckb_testtool::ckb_types;
 use ckb_testtool::ckb_types::core::ScriptHashType;
 use serde_json::json;
+use types::address::ckb::{AddressScript, Network};
+use types::cell_data::{with_format, with_prefix, DataFormat, DAPP_DATA_PREFIX_LEN};
+use types::config::{
+    Config, FLAG_CANONICAL_JSON, FLAG_IMMUTABLE, FLAG_REQUIRE_FORMAT_TAG, FLAG_SORTED_KEYS,
+    FLAG_TYPE_ID, SECTION_ADDRESSES,
+};
 use types::kuai_mvp_data::{Data, KuaiMvpView};
+use types::record_reader::MAX_UNSORTED_ENTRIES;
+use types::operation::Operation;
 
 const MAX_CYCLES: u64 = 10_000_000;
 
 // error numbers
-const ERROR_EMPTY_ARGS: i8 = 5;
+const ERROR_CKB_ADDRESS_EMPTY: i8 = 5;
+const ERROR_DATA_PARSE: i8 = 6;
+const ERROR_DATA_TOO_SHORT: i8 = 8;
+const ERROR_DATA_PREFIX_MISMATCH: i8 = 9;
+const ERROR_LOCK_CHANGED: i8 = 10;
+const ERROR_INVALID_CKB_ADDRESS: i8 = 12;
+const ERROR_CKB_ADDRESS_LOCK_MISMATCH: i8 = 13;
+const ERROR_RECORD_COUNT_MISMATCH: i8 = 14;
+const ERROR_REQUIRED_ENTRY_REMOVED: i8 = 15;
+const ERROR_ORPHANED_RECORD: i8 = 17;
+const ERROR_NOT_TYPE_SCRIPT: i8 = 18;
+const ERROR_INVALID_UTF8: i8 = 20;
+const ERROR_INVALID_MOLECULE: i8 = 21;
+const ERROR_MISSING_DATA_FORMAT: i8 = 22;
+const ERROR_UNKNOWN_DATA_FORMAT: i8 = 23;
+const ERROR_INVALID_CONFIG: i8 = 24;
+const ERROR_TOO_MANY_ENTRIES: i8 = 25;
+const ERROR_VALUE_TOO_LONG: i8 = 27;
+const ERROR_DATA_TOO_LARGE: i8 = 28;
+const ERROR_SECTION_NOT_ALLOWED: i8 = 29;
+const ERROR_DUPLICATE_RECORD: i8 = 30;
+const ERROR_TYPE_ID_MISMATCH: i8 = 31;
+const ERROR_IMMUTABLE_RECORD: i8 = 32;
+const ERROR_LOCK_IN_PERIOD: i8 = 33;
+const ERROR_DUPLICATE_KEY: i8 = 34;
+const ERROR_UNSORTED_KEYS: i8 = 35;
+const ERROR_INVALID_ETH_ADDRESS: i8 = 36;
+const ERROR_INVALID_BTC_ADDRESS: i8 = 37;
+const ERROR_INVALID_DWEB_ENTRY: i8 = 38;
+const ERROR_NON_CANONICAL_JSON: i8 = 39;
+const ERROR_UNKNOWN_LABEL: i8 = 40;
+const ERROR_REQUIRED_NOT_ALLOWED: i8 = 41;
+const ERROR_VERIFIED_VALUE_CHANGED: i8 = 42;
 
 fn assert_script_error(err: Error, err_code: i8) {
     let error_string = err.to_string();
@@ -29,6 +70,260 @@ fn assert_script_error(err: Error, err_code: i8) {
     );
 }
 
+// build a transaction creating one record cell with the given data
+fn build_record_tx(context: &mut Context, record_data: Bytes) -> TransactionView {
+    build_binary_record_tx(context, "kuai-mvp-contract", record_data)
+}
+
+// same as `build_record_tx` with the contract loaded from the `binary` build
+fn build_binary_record_tx(
+    context: &mut Context,
+    binary: &str,
+    record_data: Bytes,
+) -> TransactionView {
+    let contract_bin: Bytes = Loader::default().load_binary(binary);
+    let out_point = context.deploy_cell(contract_bin);
+
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    let lock_script = context
+        .build_script(&always_success_out_point, Bytes::new())
+        .expect("script");
+    let type_script = context
+        .build_script(&out_point, Bytes::new())
+        .expect("script");
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
+    let lock_script_dep = CellDep::new_builder()
+        .out_point(always_success_out_point)
+        .build();
+
+    let input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let input = CellInput::new_builder()
+        .previous_output(input_out_point)
+        .build();
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script.clone())
+            .type_(Some(type_script).pack())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script)
+            .build(),
+    ];
+    let outputs_data = vec![record_data, Bytes::new()];
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
+        .cell_dep(lock_script_dep)
+        .build();
+    context.complete_tx(tx)
+}
+
+// the lock used by `build_record_tx`
+fn record_lock(context: &mut Context) -> Script {
+    let out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    context
+        .build_script(&out_point, Bytes::new())
+        .expect("script")
+}
+
+// an always-success lock with the owner as args
+fn owner_lock(context: &mut Context, owner: u8) -> Script {
+    let out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+    context
+        .build_script(&out_point, Bytes::from(vec![owner]))
+        .expect("script")
+}
+
+fn lock_address(lock: &Script) -> String {
+    let mut code_hash = [0u8; 32];
+    code_hash.copy_from_slice(lock.code_hash().as_slice());
+    AddressScript {
+        code_hash,
+        hash_type: lock.hash_type().as_slice()[0],
+        args: lock.args().raw_data().to_vec(),
+    }
+    .to_address(Network::Testnet)
+}
+
+fn owner_address(context: &mut Context, owner: u8) -> String {
+    let lock = owner_lock(context, owner);
+    lock_address(&lock)
+}
+
+// a record with only the "ckb" entry
+fn record_view(ckb_value: &str) -> KuaiMvpView {
+    KuaiMvpView {
+        addresses: vec![Data {
+            key: String::from("ckb"),
+            value: String::from(ckb_value),
+            label: String::from("required"),
+        }],
+        ..Default::default()
+    }
+}
+
+fn view_data(view: &KuaiMvpView) -> Bytes {
+    let json_str = serde_json::to_string(view).unwrap();
+    Bytes::from(with_prefix(json_str.as_bytes()))
+}
+
+fn record_data(ckb_value: &str) -> Bytes {
+    view_data(&record_view(ckb_value))
+}
+
+// build a transaction spending and creating record cells, a cell is described by `(owner, data)`
+// and the owner is used as the args of an always-success lock
+fn build_transition_tx(
+    context: &mut Context,
+    inputs: Vec<(u8, Bytes)>,
+    outputs: Vec<(u8, Bytes)>,
+    witnesses: Vec<Bytes>,
+) -> TransactionView {
+    build_config_tx(context, Bytes::new(), inputs, outputs, witnesses)
+}
+
+// same as `build_transition_tx` with `args` as the args of the type script
+fn build_config_tx(
+    context: &mut Context,
+    args: Bytes,
+    inputs: Vec<(u8, Bytes)>,
+    outputs: Vec<(u8, Bytes)>,
+    witnesses: Vec<Bytes>,
+) -> TransactionView {
+    let contract_bin: Bytes = Loader::default().load_binary("kuai-mvp-contract");
+    let out_point = context.deploy_cell(contract_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let type_script = context
+        .build_script(&out_point, args)
+        .expect("script");
+
+    let mut input_cells = Vec::new();
+    for (owner, data) in inputs {
+        let cell = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(owner_lock(context, owner))
+            .type_(Some(type_script.clone()).pack())
+            .build();
+        input_cells.push(
+            CellInput::new_builder()
+                .previous_output(context.create_cell(cell, data))
+                .build(),
+        );
+    }
+    // a plain cell paying for the records, so the transaction always has an input
+    let funding_cell = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(owner_lock(context, 0))
+        .build();
+    input_cells.push(
+        CellInput::new_builder()
+            .previous_output(context.create_cell(funding_cell, Bytes::new()))
+            .build(),
+    );
+
+    let mut output_cells = Vec::new();
+    let mut outputs_data = Vec::new();
+    for (owner, data) in outputs {
+        output_cells.push(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(owner_lock(context, owner))
+                .type_(Some(type_script.clone()).pack())
+                .build(),
+        );
+        outputs_data.push(data);
+    }
+
+    let tx = TransactionBuilder::default()
+        .inputs(input_cells)
+        .outputs(output_cells)
+        .outputs_data(outputs_data.pack())
+        .witnesses(witnesses.pack())
+        .cell_dep(CellDep::new_builder().out_point(out_point).build())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .build();
+    context.complete_tx(tx)
+}
+
+// build a transaction creating records of a type id deployment, every output gets the identifier
+// derived from the first input and `id_index`
+fn build_type_id_tx(
+    context: &mut Context,
+    outputs: Vec<(u8, Bytes)>,
+    id_index: u64,
+) -> TransactionView {
+    let contract_bin: Bytes = Loader::default().load_binary("kuai-mvp-contract");
+    let out_point = context.deploy_cell(contract_bin);
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+
+    let funding_cell = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(owner_lock(context, 0))
+        .build();
+    let input = CellInput::new_builder()
+        .previous_output(context.create_cell(funding_cell, Bytes::new()))
+        .build();
+
+    let mut preimage = input.as_slice().to_vec();
+    preimage.extend_from_slice(&id_index.to_le_bytes());
+    let config = Config {
+        flags: FLAG_TYPE_ID,
+        ..Default::default()
+    };
+    let type_script = context
+        .build_script(&out_point, config.as_type_id_args(&blake2b_256(preimage)))
+        .expect("script");
+
+    let mut output_cells = Vec::new();
+    let mut outputs_data = Vec::new();
+    for (owner, data) in outputs {
+        output_cells.push(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(owner_lock(context, owner))
+                .type_(Some(type_script.clone()).pack())
+                .build(),
+        );
+        outputs_data.push(data);
+    }
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .outputs(output_cells)
+        .outputs_data(outputs_data.pack())
+        .cell_dep(CellDep::new_builder().out_point(out_point).build())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point)
+                .build(),
+        )
+        .build();
+    context.complete_tx(tx)
+}
+
+fn operation_witness(operation: Operation) -> Bytes {
+    WitnessArgs::new_builder()
+        .input_type(Some(Bytes::from(operation.as_input_type().to_vec())).pack())
+        .build()
+        .as_bytes()
+}
+
 #[test]
 fn test_success() {
     // deploy contract
@@ -37,11 +332,16 @@ fn test_success() {
     let out_point = context.deploy_cell(contract_bin);
 
     // prepare scripts
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
     let lock_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&always_success_out_point, Bytes::new())
+        .expect("script");
+    let type_script = context
+        .build_script(&out_point, Bytes::new())
         .expect("script");
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
     let lock_script_dep = CellDep::new_builder()
-        .out_point(out_point)
+        .out_point(always_success_out_point)
         .build();
 
     // prepare cells
@@ -56,10 +356,12 @@ fn test_success() {
     let input = CellInput::new_builder()
         .previous_output(input_out_point)
         .build();
+    let ckb_address = lock_address(&lock_script);
     let outputs = vec![
         CellOutput::new_builder()
             .capacity(500u64.pack())
             .lock(lock_script.clone())
+            .type_(Some(type_script).pack())
             .build(),
         CellOutput::new_builder()
             .capacity(500u64.pack())
@@ -71,28 +373,37 @@ fn test_success() {
 
     addresses.push(Data {
         key: String::from("ckb"),
-        value: String::from("123"),
+        value: ckb_address,
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("1234"),
         value: String::from("1234"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("12345"),
         value: String::from("12345"),
+        label: String::new(),
     });
 
-    let x = KuaiMvpView { addresses: addresses };
+    let x = KuaiMvpView {
+        addresses,
+        ..Default::default()
+    };
 
     let bytes = x.as_molecule_data().unwrap();
 
-    let outputs_data = vec![bytes, Bytes::new()];
+    let data = with_format(DataFormat::Molecule, &bytes);
+
+    let outputs_data = vec![Bytes::from(data), Bytes::new()];
 
     // build transaction
     let tx = TransactionBuilder::default()
         .input(input)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
         .cell_dep(lock_script_dep)
         .build();
     let tx = context.complete_tx(tx);
@@ -112,11 +423,16 @@ fn test_error() {
     let out_point = context.deploy_cell(contract_bin);
 
     // prepare scripts
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
     let lock_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&always_success_out_point, Bytes::new())
         .expect("script");
+    let type_script = context
+        .build_script(&out_point, Bytes::new())
+        .expect("script");
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
     let lock_script_dep = CellDep::new_builder()
-        .out_point(out_point)
+        .out_point(always_success_out_point)
         .build();
 
     // prepare cells
@@ -135,6 +451,7 @@ fn test_error() {
         CellOutput::new_builder()
             .capacity(500u64.pack())
             .lock(lock_script.clone())
+            .type_(Some(type_script).pack())
             .build(),
         CellOutput::new_builder()
             .capacity(500u64.pack())
@@ -147,36 +464,43 @@ fn test_error() {
     addresses.push(Data {
         key: String::from("test"),
         value: String::from("123"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("1234"),
         value: String::from("1234"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("12345"),
         value: String::from("12345"),
+        label: String::new(),
     });
 
-    let x = KuaiMvpView { addresses: addresses };
+    let x = KuaiMvpView {
+        addresses,
+        ..Default::default()
+    };
 
     let bytes = x.as_molecule_data().unwrap();
 
-    let outputs_data = vec![bytes, Bytes::new()];
+    let data = with_format(DataFormat::Molecule, &bytes);
+
+    let outputs_data = vec![Bytes::from(data), Bytes::new()];
 
     // build transaction
     let tx = TransactionBuilder::default()
         .input(input)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
         .cell_dep(lock_script_dep)
         .build();
     let tx = context.complete_tx(tx);
 
     // run
-    let cycles = context
-        .verify_tx(&tx, MAX_CYCLES)
-        .expect("pass verification");
-    println!("consume cycles: {}", cycles);
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_CKB_ADDRESS_EMPTY);
 }
 
 #[test]
@@ -187,16 +511,18 @@ fn test_json_success() {
     let out_point = context.deploy_cell(contract_bin);
 
     // prepare scripts
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
     let lock_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&always_success_out_point, Bytes::new())
         .expect("script");
 
     let type_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&out_point, Bytes::new())
         .expect("script");
 
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
     let lock_script_dep = CellDep::new_builder()
-        .out_point(out_point)
+        .out_point(always_success_out_point)
         .build();
 
     // prepare cells
@@ -227,18 +553,24 @@ fn test_json_success() {
 
     addresses.push(Data {
         key: String::from("ckb"),
-        value: String::from("123"),
+        value: lock_address(&lock_script),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("1234"),
         value: String::from("1234"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("12345"),
         value: String::from("12345"),
+        label: String::new(),
     });
 
-    let x = KuaiMvpView { addresses: addresses };
+    let x = KuaiMvpView {
+        addresses,
+        ..Default::default()
+    };
     let jsonStr = serde_json::to_string(&x).unwrap();
 
     let mut data: Vec<u8> = Vec::new();
@@ -252,6 +584,7 @@ fn test_json_success() {
         .input(input)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
         .cell_dep(lock_script_dep)
         .build();
     let tx = context.complete_tx(tx);
@@ -271,16 +604,18 @@ fn test_json_error1() {
     let out_point = context.deploy_cell(contract_bin);
 
     // prepare scripts
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
     let lock_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&always_success_out_point, Bytes::new())
         .expect("script");
 
     let type_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&out_point, Bytes::new())
         .expect("script");
 
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
     let lock_script_dep = CellDep::new_builder()
-        .out_point(out_point)
+        .out_point(always_success_out_point)
         .build();
 
     // prepare cells
@@ -313,17 +648,23 @@ fn test_json_error1() {
     addresses.push(Data {
         key: String::from("ckb"),
         value: String::from("123"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("1234"),
         value: String::from("1234"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("12345"),
         value: String::from("12345"),
+        label: String::new(),
     });
 
-    let x = KuaiMvpView { addresses: addresses };
+    let x = KuaiMvpView {
+        addresses,
+        ..Default::default()
+    };
     let jsonStr = serde_json::to_string(&x).unwrap();
     println!("{:?}", jsonStr);
 
@@ -338,15 +679,15 @@ fn test_json_error1() {
         .input(input)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
         .cell_dep(lock_script_dep)
         .build();
     let tx = context.complete_tx(tx);
 
     // run
-    let cycles = context
-        .verify_tx(&tx, MAX_CYCLES)
-        .expect("pass verification");
-    println!("consume cycles: {}", cycles);
+    // "123" is not a CKB address, the first record fails before the empty one is read
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_CKB_ADDRESS);
 }
 
 #[test]
@@ -357,16 +698,18 @@ fn test_json_error2() {
     let out_point = context.deploy_cell(contract_bin);
 
     // prepare scripts
+    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
     let lock_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&always_success_out_point, Bytes::new())
         .expect("script");
 
     let type_script = context
-        .build_script(&out_point, Bytes::from(vec![42]))
+        .build_script(&out_point, Bytes::new())
         .expect("script");
 
+    let contract_dep = CellDep::new_builder().out_point(out_point).build();
     let lock_script_dep = CellDep::new_builder()
-        .out_point(out_point)
+        .out_point(always_success_out_point)
         .build();
 
     // prepare cells
@@ -398,17 +741,23 @@ fn test_json_error2() {
     addresses.push(Data {
         key: String::from("eth"),
         value: String::from("123"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("1234"),
         value: String::from("1234"),
+        label: String::new(),
     });
     addresses.push(Data {
         key: String::from("12345"),
         value: String::from("12345"),
+        label: String::new(),
     });
 
-    let x = KuaiMvpView { addresses: addresses };
+    let x = KuaiMvpView {
+        addresses,
+        ..Default::default()
+    };
     let jsonStr = serde_json::to_string(&x).unwrap();
     println!("{:?}", jsonStr);
 
@@ -423,67 +772,1007 @@ fn test_json_error2() {
         .input(input)
         .outputs(outputs)
         .outputs_data(outputs_data.pack())
+        .cell_dep(contract_dep)
         .cell_dep(lock_script_dep)
         .build();
     let tx = context.complete_tx(tx);
 
     // run
+    // the "eth" entry is checked as an Ethereum address before the missing "ckb" entry
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_ETH_ADDRESS);
+}
+
+#[test]
+fn test_data_too_short() {
+    let mut context = Context::default();
+    let tx = build_record_tx(&mut context, Bytes::from("mvp"));
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_TOO_SHORT);
+}
+
+#[test]
+fn test_data_prefix_mismatch() {
+    let mut context = Context::default();
+    let json_str = r#"{"addresses":[{"key":"ckb","value":"123"}]}"#;
+    let mut data: Vec<u8> = Vec::new();
+    data.extend("mvp-daap".as_bytes());
+    data.extend(json_str.as_bytes());
+    let tx = build_record_tx(&mut context, Bytes::from(data));
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_PREFIX_MISMATCH);
+}
+
+#[test]
+fn test_data_with_prefix() {
+    let mut context = Context::default();
+    let lock = record_lock(&mut context);
+    let json_str = format!(
+        r#"{{"addresses":[{{"key":"ckb","value":"{}"}}]}}"#,
+        lock_address(&lock)
+    );
+    let tx = build_record_tx(&mut context, Bytes::from(with_prefix(json_str.as_bytes())));
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_keeps_lock() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, record_data(&owner))],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_changes_lock() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let new_owner = owner_address(&mut context, 2);
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, record_data(&owner))],
+        vec![(2, record_data(&new_owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_LOCK_CHANGED);
+}
+
+#[test]
+fn test_transfer_changes_lock() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let new_owner = owner_address(&mut context, 2);
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, record_data(&owner))],
+        vec![(2, record_data(&new_owner))],
+        vec![operation_witness(Operation::Transfer)],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_invalid_ckb_address() {
+    let mut context = Context::default();
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, record_data("123"))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_CKB_ADDRESS);
+}
+
+#[test]
+fn test_ckb_address_of_another_lock() {
+    let mut context = Context::default();
+    let other = owner_address(&mut context, 2);
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, record_data(&other))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_CKB_ADDRESS_LOCK_MISMATCH);
+}
+
+#[test]
+fn test_update_removes_required_entry() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut old_view = record_view(&owner);
+    old_view.profile.push(Data {
+        key: String::from("name"),
+        value: String::from("kuai"),
+        label: String::from("required"),
+    });
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, view_data(&old_view))],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_REQUIRED_ENTRY_REMOVED);
+}
+
+#[test]
+fn test_update_unlabels_required_entry() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    let old_data = view_data(&view);
+    view.addresses[0].label = String::from("public");
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, old_data)],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_REQUIRED_ENTRY_REMOVED);
+}
+
+#[test]
+fn test_labels() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    for (key, label) in [("name", "verified"), ("email", "private"), ("site", "public")] {
+        view.profile.push(Data {
+            key: String::from(key),
+            value: String::from("kuai"),
+            label: String::from(label),
+        });
+    }
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
     let cycles = context
         .verify_tx(&tx, MAX_CYCLES)
         .expect("pass verification");
     println!("consume cycles: {}", cycles);
+
+    view.profile[2].label = String::from("official");
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_UNKNOWN_LABEL);
+}
+
+#[test]
+fn test_required_entry_in_dweb() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.dweb.push(Data {
+        key: String::from("ipfs"),
+        value: String::from("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"),
+        label: String::from("required"),
+    });
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_REQUIRED_NOT_ALLOWED);
 }
 
-//
-// #[test]
-// fn test_empty_args() {
-//     // deploy contract
-//     let mut context = Context::default();
-//     let contract_bin: Bytes = Loader::default().load_binary("kuai-mvp-contract");
-//     let out_point = context.deploy_cell(contract_bin);
-//
-//     // prepare scripts
-//     let lock_script = context
-//         .build_script(&out_point, Default::default())
-//         .expect("script");
-//     let lock_script_dep = CellDep::new_builder()
-//         .out_point(out_point)
-//         .build();
-//
-//     // prepare cells
-//     let input_out_point = context.create_cell(
-//         CellOutput::new_builder()
-//             .capacity(1000u64.pack())
-//             .lock(lock_script.clone())
-//             .build(),
-//         Bytes::new(),
-//     );
-//     let input = CellInput::new_builder()
-//         .previous_output(input_out_point)
-//         .build();
-//     let outputs = vec![
-//         CellOutput::new_builder()
-//             .capacity(500u64.pack())
-//             .lock(lock_script.clone())
-//             .build(),
-//         CellOutput::new_builder()
-//             .capacity(500u64.pack())
-//             .lock(lock_script)
-//             .build(),
-//     ];
-//
-//     let outputs_data = vec![Bytes::new(); 2];
-//
-//     // build transaction
-//     let tx = TransactionBuilder::default()
-//         .input(input)
-//         .outputs(outputs)
-//         .outputs_data(outputs_data.pack())
-//         .cell_dep(lock_script_dep)
-//         .build();
-//     let tx = context.complete_tx(tx);
-//
-//     // run
-//     let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
-//     assert_script_error(err, ERROR_EMPTY_ARGS);
-// }
+#[test]
+fn test_update_verified_entry() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut old_view = record_view(&owner);
+    old_view.profile.push(Data {
+        key: String::from("name"),
+        value: String::from("kuai"),
+        label: String::from("verified"),
+    });
+    let mut view = old_view.clone();
+    view.profile[0].value = String::from("kuai-js");
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, view_data(&old_view))],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_VERIFIED_VALUE_CHANGED);
+
+    // the new value is no longer verified
+    view.profile[0].label = String::new();
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, view_data(&old_view))],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_without_successor() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_transition_tx(
+        &mut context,
+        vec![
+            (1, record_data(&owner)),
+            (1, record_data(&owner)),
+            (1, record_data(&owner)),
+        ],
+        vec![(1, record_data(&owner)), (1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_RECORD_COUNT_MISMATCH);
+}
+
+// `RecordModel.update` merges every record of a lock into one output
+#[test]
+fn test_merge_update() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.profile.push(Data {
+        key: String::from("name"),
+        value: String::from("kuai"),
+        label: String::new(),
+    });
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, record_data(&owner)), (1, record_data(&owner))],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_merge_update_removes_required_entry() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.profile.push(Data {
+        key: String::from("name"),
+        value: String::from("kuai"),
+        label: String::from("required"),
+    });
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, record_data(&owner)), (1, view_data(&view))],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_REQUIRED_ENTRY_REMOVED);
+}
+
+#[test]
+fn test_contract_as_lock() {
+    let mut context = Context::default();
+    let contract_bin: Bytes = Loader::default().load_binary("kuai-mvp-contract");
+    let out_point = context.deploy_cell(contract_bin);
+    let lock_script = context
+        .build_script(&out_point, Bytes::new())
+        .expect("script");
+
+    let input_out_point = context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let input = CellInput::new_builder()
+        .previous_output(input_out_point)
+        .build();
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock_script)
+        .build();
+
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .cell_dep(CellDep::new_builder().out_point(out_point).build())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_NOT_TYPE_SCRIPT);
+}
+
+#[test]
+fn test_destroy() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_transition_tx(&mut context, vec![(1, record_data(&owner))], vec![], vec![]);
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_destroy_leaves_orphaned_record() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_transition_tx(&mut context, vec![(1, record_data(&owner))], vec![], vec![]);
+    let orphan = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(owner_lock(&mut context, 1))
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .output(orphan)
+        .output_data(record_data(&owner).pack())
+        .build();
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_ORPHANED_RECORD);
+}
+
+#[test]
+fn test_invalid_json() {
+    let mut context = Context::default();
+    let data = Bytes::from(with_prefix(br#"{"addresses":[{"key":"ckb""#));
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, data)], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_PARSE);
+}
+
+#[test]
+fn test_invalid_utf8() {
+    let mut context = Context::default();
+    let data = Bytes::from(with_prefix(&[b'{', 0xff, b'}']));
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, data)], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_UTF8);
+}
+
+#[test]
+fn test_unknown_data_format() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut data = with_format(DataFormat::Molecule, &record_view(&owner).as_molecule_data().unwrap());
+    data[DAPP_DATA_PREFIX_LEN] = 0x02;
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, Bytes::from(data))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_UNKNOWN_DATA_FORMAT);
+}
+
+#[test]
+fn test_molecule_update() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let view = record_view(&owner);
+    let json_data = view_data(&view);
+    let molecule_data = with_format(DataFormat::Molecule, &view.as_molecule_data().unwrap());
+    let tx = build_transition_tx(
+        &mut context,
+        vec![(1, json_data)],
+        vec![(1, Bytes::from(molecule_data))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_broken_molecule() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut record = record_view(&owner).as_molecule_data().unwrap().to_vec();
+    record.truncate(record.len() - 1);
+    let data = with_format(DataFormat::Molecule, &record);
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, Bytes::from(data))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_MOLECULE);
+}
+
+#[test]
+fn test_invalid_config() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_config_tx(
+        &mut context,
+        Bytes::from(vec![42]),
+        vec![],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_CONFIG);
+}
+
+#[test]
+fn test_config_within_limits() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        max_entries: 1,
+        max_value_length: owner.len() as u32,
+        max_data_size: 1024,
+        allowed_sections: SECTION_ADDRESSES,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_config_too_many_entries() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.addresses.push(Data {
+        key: String::from("dot"),
+        value: String::from("1"),
+        label: String::new(),
+    });
+    let config = Config {
+        max_entries: 1,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_TOO_MANY_ENTRIES);
+}
+
+#[test]
+fn test_config_value_too_long() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        max_value_length: owner.len() as u32 - 1,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_VALUE_TOO_LONG);
+}
+
+#[test]
+fn test_config_data_too_large() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let data = record_data(&owner);
+    let config = Config {
+        max_data_size: data.len() as u32 - 1,
+        ..Default::default()
+    };
+    let tx = build_config_tx(&mut context, config.as_args(), vec![], vec![(1, data)], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_TOO_LARGE);
+}
+
+#[test]
+fn test_config_section_not_allowed() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.custom.push(Data {
+        key: String::from("note"),
+        value: String::from("hello"),
+        label: String::new(),
+    });
+    let config = Config {
+        allowed_sections: SECTION_ADDRESSES,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_SECTION_NOT_ALLOWED);
+}
+
+#[test]
+fn test_config_requires_format_tag() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        flags: FLAG_REQUIRE_FORMAT_TAG,
+        ..Default::default()
+    };
+    let args = config.as_args();
+    let tx = build_config_tx(
+        &mut context,
+        args.clone(),
+        vec![],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_MISSING_DATA_FORMAT);
+
+    // legacy records already on chain can still be migrated
+    let json_str = serde_json::to_string(&record_view(&owner)).unwrap();
+    let data = with_format(DataFormat::Json, json_str.as_bytes());
+    let tx = build_config_tx(
+        &mut context,
+        args,
+        vec![(1, record_data(&owner))],
+        vec![(1, Bytes::from(data))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_type_id_create() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_type_id_tx(&mut context, vec![(1, record_data(&owner))], 0);
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_type_id_mismatch() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_type_id_tx(&mut context, vec![(1, record_data(&owner))], 1);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_TYPE_ID_MISMATCH);
+}
+
+#[test]
+fn test_type_id_duplicate_record() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let tx = build_type_id_tx(
+        &mut context,
+        vec![(1, record_data(&owner)), (1, record_data(&owner))],
+        0,
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DUPLICATE_RECORD);
+}
+
+#[test]
+fn test_type_id_update() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        flags: FLAG_TYPE_ID,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_type_id_args(&[7u8; 32]),
+        vec![(1, record_data(&owner))],
+        vec![(1, record_data(&owner))],
+        vec![],
+    );
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_destroy_immutable_record() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        flags: FLAG_IMMUTABLE,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![(1, record_data(&owner))],
+        vec![],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_IMMUTABLE_RECORD);
+}
+
+#[test]
+fn test_destroy_in_lock_in_period() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        // relative, 100 blocks
+        lock_in_period: 0x8000_0000_0000_0064,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![(1, record_data(&owner))],
+        vec![],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_LOCK_IN_PERIOD);
+}
+
+#[test]
+fn test_duplicate_key() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.addresses.push(Data {
+        key: String::from("ckb"),
+        value: owner.clone(),
+        label: String::new(),
+    });
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DUPLICATE_KEY);
+}
+
+#[test]
+fn test_config_sorted_keys() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let entry = |key: &str| Data {
+        key: String::from(key),
+        value: String::from("value"),
+        label: String::new(),
+    };
+    let config = Config {
+        flags: FLAG_SORTED_KEYS,
+        ..Default::default()
+    };
+
+    let mut view = record_view(&owner);
+    view.addresses.extend([entry("dot"), entry("sol")]);
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    view.addresses.swap(1, 2);
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_UNSORTED_KEYS);
+}
+
+// sections larger than `MAX_UNSORTED_ENTRIES` need sorted keys even without `FLAG_SORTED_KEYS`,
+// in both formats
+#[test]
+fn test_large_section_unsorted() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    for index in (0..MAX_UNSORTED_ENTRIES + 1).rev() {
+        view.custom.push(Data {
+            key: format!("key{:03}", index),
+            value: String::new(),
+            label: String::new(),
+        });
+    }
+    let molecule = with_format(DataFormat::Molecule, &view.as_molecule_data().unwrap());
+
+    for data in [view_data(&view), Bytes::from(molecule)] {
+        let tx = build_transition_tx(&mut context, vec![], vec![(1, data)], vec![]);
+        let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+        assert_script_error(err, ERROR_UNSORTED_KEYS);
+    }
+}
+
+#[test]
+fn test_eth_address() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.addresses.push(Data {
+        key: String::from("eth"),
+        value: String::from("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+        label: String::new(),
+    });
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    view.addresses[1].value = String::from("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD");
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_ETH_ADDRESS);
+}
+
+#[test]
+fn test_btc_address() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.addresses.push(Data {
+        key: String::from("btc"),
+        value: String::from("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+        label: String::new(),
+    });
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // a typo in the last character breaks the checksum
+    view.addresses[1].value = String::from("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5");
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_BTC_ADDRESS);
+}
+
+#[test]
+fn test_dweb_entries() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut view = record_view(&owner);
+    view.dweb.push(Data {
+        key: String::from("ipfs"),
+        value: String::from("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"),
+        label: String::new(),
+    });
+    view.dweb.push(Data {
+        key: String::from("arweave"),
+        value: String::from("bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U"),
+        label: String::new(),
+    });
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    view.dweb[0].value = String::from("ipfs://QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o");
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, view_data(&view))], vec![]);
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_INVALID_DWEB_ENTRY);
+}
+
+#[test]
+fn test_config_canonical_json() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let view = record_view(&owner);
+    let config = Config {
+        flags: FLAG_CANONICAL_JSON,
+        ..Default::default()
+    };
+
+    let canonical = view.as_canonical_json().unwrap();
+    let data = Bytes::from(with_format(DataFormat::Json, canonical.as_bytes()));
+    let tx = build_config_tx(&mut context, config.as_args(), vec![], vec![(1, data)], vec![]);
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // `serde_json` keeps the order of the struct fields, `value` before `label`
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&view))],
+        vec![],
+    );
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_NON_CANONICAL_JSON);
+}
+
+// The contract built with `--features serde-json` is expected in the build directory as
+// `kuai-mvp-contract-serde-json`, see the README. The default build creates the record without
+// allocating, through `record_stream`
+#[test]
+#[ignore]
+fn test_json_record_cycles() {
+    let mut cycles = Vec::new();
+    for binary in ["kuai-mvp-contract", "kuai-mvp-contract-serde-json"] {
+        let mut context = Context::default();
+        let lock = record_lock(&mut context);
+        let mut view = record_view(&lock_address(&lock));
+        for index in 0..8 {
+            view.profile.push(Data {
+                key: format!("name{}", index),
+                value: String::from("caf\u{e9} \"kuai\"\n"),
+                label: String::new(),
+            });
+            view.custom.push(Data {
+                key: format!("link{}", index),
+                value: String::from("https://github.com/ckb-js/kuai"),
+                label: String::from("public"),
+            });
+        }
+        let tx = build_binary_record_tx(&mut context, binary, view_data(&view));
+
+        cycles.push(
+            context
+                .verify_tx(&tx, MAX_CYCLES)
+                .expect("pass verification"),
+        );
+    }
+
+    println!("record_stream cycles: {}, serde_json cycles: {}", cycles[0], cycles[1]);
+    assert!(cycles[0] < cycles[1]);
+}
+
+// a record larger than the windows of `loader` with many custom entries
+fn large_record_view(ckb_value: &str) -> KuaiMvpView {
+    let mut view = record_view(ckb_value);
+    for index in 0..64 {
+        view.custom.push(Data {
+            key: format!("link{:02}", index),
+            value: String::from("https://github.com/ckb-js/kuai"),
+            label: String::new(),
+        });
+    }
+    view
+}
+
+#[test]
+fn test_large_json_record() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let data = view_data(&large_record_view(&owner));
+    assert!(data.len() > 3 * 1024);
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, data)], vec![]);
+
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_large_json_record_broken_tail() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let mut data = view_data(&large_record_view(&owner)).to_vec();
+    data.pop();
+    let tx = build_transition_tx(&mut context, vec![], vec![(1, Bytes::from(data))], vec![]);
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_PARSE);
+}
+
+#[test]
+fn test_large_json_record_too_many_entries() {
+    let mut context = Context::default();
+    let owner = owner_address(&mut context, 1);
+    let config = Config {
+        max_entries: 32,
+        ..Default::default()
+    };
+    let tx = build_config_tx(
+        &mut context,
+        config.as_args(),
+        vec![],
+        vec![(1, view_data(&large_record_view(&owner)))],
+        vec![],
+    );
+
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_TOO_MANY_ENTRIES);
+}
+
+// needs the `debug-log` build of the contract copied to `build/debug` as
+// `kuai-mvp-contract-debug-log`, see the README
+#[test]
+#[ignore]
+fn test_debug_log() {
+    for binary in ["kuai-mvp-contract", "kuai-mvp-contract-debug-log"] {
+        let mut context = Context::default();
+        context.set_capture_debug(true);
+        let lock = record_lock(&mut context);
+        let tx = build_binary_record_tx(&mut context, binary, record_data(&lock_address(&lock)));
+        context
+            .verify_tx(&tx, MAX_CYCLES)
+            .expect("pass verification");
+
+        let traced = context
+            .captured_messages()
+            .iter()
+            .any(|message| message.message == "kuai-mvp: output#0 record ok");
+        assert_eq!(traced, binary.ends_with("debug-log"));
+    }
+
+    let mut context = Context::default();
+    context.set_capture_debug(true);
+    let tx = build_binary_record_tx(
+        &mut context,
+        "kuai-mvp-contract-debug-log",
+        Bytes::from(with_prefix(b"{")),
+    );
+    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    assert_script_error(err, ERROR_DATA_PARSE);
+    assert!(context
+        .captured_messages()
+        .iter()
+        .any(|message| message.message == "kuai-mvp: output#0 record err DataParseError (6)"));
+}
@@ -0,0 +1,62 @@
+//! Base58 with the Bitcoin alphabet and Base58Check.
+
+use crate::hash::sha256::sha256d;
+use alloc::vec::Vec;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base58Error {
+    InvalidChar,
+    TooShort,
+    InvalidChecksum,
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, Base58Error> {
+    decode_alphabet(s, ALPHABET)
+}
+
+/// Decode `s` as a big endian number in the base of `alphabet`, base36 of multibase also reads
+/// this way. Every leading first digit of the alphabet stands for a zero byte.
+pub fn decode_alphabet(s: &str, alphabet: &[u8]) -> Result<Vec<u8>, Base58Error> {
+    let base = alphabet.len() as u32;
+
+    // big endian bytes of the number, without the leading zeros
+    let mut number: Vec<u8> = Vec::new();
+    for c in s.bytes() {
+        let mut carry = alphabet
+            .iter()
+            .position(|a| *a == c)
+            .ok_or(Base58Error::InvalidChar)? as u32;
+        for byte in number.iter_mut().rev() {
+            carry += (*byte as u32) * base;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            number.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = s.bytes().take_while(|c| *c == alphabet[0]).count();
+    let mut bytes = Vec::with_capacity(zeros + number.len());
+    bytes.resize(zeros, 0);
+    bytes.extend_from_slice(&number);
+    Ok(bytes)
+}
+
+/// Decode and strip the 4 bytes double SHA-256 checksum
+pub fn decode_check(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut bytes = decode(s)?;
+    if bytes.len() < 4 {
+        return Err(Base58Error::TooShort);
+    }
+
+    let payload_len = bytes.len() - 4;
+    if sha256d(&bytes[..payload_len])[..4] != bytes[payload_len..] {
+        return Err(Base58Error::InvalidChecksum);
+    }
+    bytes.truncate(payload_len);
+    Ok(bytes)
+}
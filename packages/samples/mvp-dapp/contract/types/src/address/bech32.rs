@@ -0,0 +1,151 @@
+//! Bech32 and bech32m codec, see BIP-173 and BIP-350.
+//!
+//! The 90 characters limit is not enforced because full CKB addresses are longer than that.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const CHECKSUM_LEN: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(&self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bech32Error {
+    MissingSeparator,
+    InvalidHrp,
+    InvalidChar,
+    MixedCase,
+    InvalidLength,
+    InvalidChecksum,
+    InvalidPadding,
+}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut chk: u32 = 1;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    hrp.iter()
+        .map(|c| c >> 5)
+        .chain(core::iter::once(0))
+        .chain(hrp.iter().map(|c| c & 0x1f))
+}
+
+/// Decode a bech32 or bech32m string into its lowercase hrp and the 5-bit groups of its data part
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Bech32Error> {
+    let bytes = s.as_bytes();
+    let has_lower = bytes.iter().any(|c| c.is_ascii_lowercase());
+    let has_upper = bytes.iter().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(Bech32Error::MixedCase);
+    }
+
+    let separator = bytes
+        .iter()
+        .rposition(|c| *c == b'1')
+        .ok_or(Bech32Error::MissingSeparator)?;
+    let (hrp, data) = (&bytes[..separator], &bytes[separator + 1..]);
+    if hrp.is_empty() || hrp.iter().any(|c| !(33..=126).contains(c)) {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    if data.len() < CHECKSUM_LEN {
+        return Err(Bech32Error::InvalidLength);
+    }
+
+    let hrp: Vec<u8> = hrp.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let mut values = Vec::with_capacity(data.len());
+    for c in data {
+        let c = c.to_ascii_lowercase();
+        let value = CHARSET
+            .iter()
+            .position(|x| *x == c)
+            .ok_or(Bech32Error::InvalidChar)?;
+        values.push(value as u8);
+    }
+
+    let variant = match polymod(hrp_expand(&hrp).chain(values.iter().copied())) {
+        chk if chk == Variant::Bech32.constant() => Variant::Bech32,
+        chk if chk == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(Bech32Error::InvalidChecksum),
+    };
+    values.truncate(values.len() - CHECKSUM_LEN);
+
+    // hrp only contains printable ascii characters
+    let hrp = String::from_utf8(hrp).map_err(|_| Bech32Error::InvalidHrp)?;
+    Ok((hrp, values, variant))
+}
+
+/// Encode 5-bit groups with a lowercase hrp
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let chk = polymod(
+        hrp_expand(hrp.as_bytes())
+            .chain(data.iter().copied())
+            .chain([0u8; CHECKSUM_LEN].iter().copied()),
+    ) ^ variant.constant();
+
+    let mut s = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LEN);
+    s.push_str(hrp);
+    s.push('1');
+    for value in data {
+        s.push(CHARSET[*value as usize] as char);
+    }
+    for i in 0..CHECKSUM_LEN {
+        s.push(CHARSET[((chk >> (5 * (5 - i))) & 0x1f) as usize] as char);
+    }
+    s
+}
+
+/// Regroup bits, e.g. from the 5-bit groups of bech32 to bytes
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Bech32Error> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut ret = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        let value = *value as u32;
+        if value >> from != 0 {
+            return Err(Bech32Error::InvalidChar);
+        }
+        acc = (acc << from) | value;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            ret.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            ret.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max_value != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+
+    Ok(ret)
+}
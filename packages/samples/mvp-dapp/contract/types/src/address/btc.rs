@@ -0,0 +1,121 @@
+//! Bitcoin addresses: Base58Check P2PKH and P2SH, and segwit addresses of BIP-173 and BIP-350.
+
+use crate::address::base58::{self, Base58Error};
+use crate::address::bech32::{self, Bech32Error, Variant};
+
+pub const HRP_MAINNET: &str = "bc";
+pub const HRP_TESTNET: &str = "tb";
+
+const VERSION_P2PKH_MAINNET: u8 = 0x00;
+const VERSION_P2SH_MAINNET: u8 = 0x05;
+const VERSION_P2PKH_TESTNET: u8 = 0x6f;
+const VERSION_P2SH_TESTNET: u8 = 0xc4;
+
+/// BIP-173 limit, unlike CKB addresses segwit addresses are bounded
+const MAX_SEGWIT_LEN: usize = 90;
+const HASH160_LEN: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressType {
+    P2pkh,
+    P2sh,
+    /// Version 0 with a 20 bytes program
+    P2wpkh,
+    /// Version 0 with a 32 bytes program
+    P2wsh,
+    /// Version 1 with a 32 bytes program
+    P2tr,
+    /// Other witness versions and programs, valid but not assigned yet
+    Segwit(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BtcAddressError {
+    Base58(Base58Error),
+    Bech32(Bech32Error),
+    UnknownVersion,
+    UnknownNetwork,
+    InvalidLength,
+    VariantMismatch,
+    InvalidProgram,
+}
+
+impl From<Base58Error> for BtcAddressError {
+    fn from(err: Base58Error) -> Self {
+        BtcAddressError::Base58(err)
+    }
+}
+
+impl From<Bech32Error> for BtcAddressError {
+    fn from(err: Bech32Error) -> Self {
+        BtcAddressError::Bech32(err)
+    }
+}
+
+fn parse_base58(address: &str) -> Result<(Network, AddressType), BtcAddressError> {
+    let payload = base58::decode_check(address)?;
+    if payload.len() != 1 + HASH160_LEN {
+        return Err(BtcAddressError::InvalidLength);
+    }
+
+    match payload[0] {
+        VERSION_P2PKH_MAINNET => Ok((Network::Mainnet, AddressType::P2pkh)),
+        VERSION_P2SH_MAINNET => Ok((Network::Mainnet, AddressType::P2sh)),
+        VERSION_P2PKH_TESTNET => Ok((Network::Testnet, AddressType::P2pkh)),
+        VERSION_P2SH_TESTNET => Ok((Network::Testnet, AddressType::P2sh)),
+        _ => Err(BtcAddressError::UnknownVersion),
+    }
+}
+
+fn parse_segwit(address: &str) -> Result<(Network, AddressType), BtcAddressError> {
+    if address.len() > MAX_SEGWIT_LEN {
+        return Err(BtcAddressError::InvalidLength);
+    }
+
+    let (hrp, data, variant) = bech32::decode(address)?;
+    let network = match hrp.as_str() {
+        HRP_MAINNET => Network::Mainnet,
+        HRP_TESTNET => Network::Testnet,
+        _ => return Err(BtcAddressError::UnknownNetwork),
+    };
+
+    let (&version, program) = data.split_first().ok_or(BtcAddressError::InvalidProgram)?;
+    if version > 16 {
+        return Err(BtcAddressError::UnknownVersion);
+    }
+    let expected_variant = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    if variant != expected_variant {
+        return Err(BtcAddressError::VariantMismatch);
+    }
+
+    let program = bech32::convert_bits(program, 5, 8, false)?;
+    let address_type = match (version, program.len()) {
+        (_, len) if !(2..=40).contains(&len) => return Err(BtcAddressError::InvalidProgram),
+        (0, 20) => AddressType::P2wpkh,
+        (0, 32) => AddressType::P2wsh,
+        (0, _) => return Err(BtcAddressError::InvalidProgram),
+        (1, 32) => AddressType::P2tr,
+        (version, _) => AddressType::Segwit(version),
+    };
+
+    Ok((network, address_type))
+}
+
+/// Segwit addresses are told apart by their hrp, anything else is read as Base58Check
+pub fn parse(address: &str) -> Result<(Network, AddressType), BtcAddressError> {
+    let lowercase = address.get(..3).map(|prefix| prefix.to_ascii_lowercase());
+    match lowercase.as_deref() {
+        Some("bc1") | Some("tb1") => parse_segwit(address),
+        _ => parse_base58(address),
+    }
+}
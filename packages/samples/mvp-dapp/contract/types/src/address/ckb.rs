@@ -0,0 +1,188 @@
+//! CKB address decoding, see RFC 0021 "CKB Address Format".
+
+use crate::address::bech32::{self, Bech32Error, Variant};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const MAINNET_HRP: &str = "ckb";
+pub const TESTNET_HRP: &str = "ckt";
+
+pub const FULL_FORMAT: u8 = 0x00;
+/// Deprecated short format with a code hash index
+pub const SHORT_FORMAT: u8 = 0x01;
+/// Deprecated full format with hash type `data`
+pub const FULL_DATA_FORMAT: u8 = 0x02;
+/// Deprecated full format with hash type `type`
+pub const FULL_TYPE_FORMAT: u8 = 0x04;
+
+pub const HASH_TYPE_DATA: u8 = 0;
+pub const HASH_TYPE_TYPE: u8 = 1;
+pub const HASH_TYPE_DATA1: u8 = 2;
+pub const HASH_TYPE_DATA2: u8 = 4;
+
+const CODE_HASH_INDEX_SECP256K1_BLAKE160: u8 = 0x00;
+const CODE_HASH_INDEX_SECP256K1_MULTISIG: u8 = 0x01;
+const CODE_HASH_INDEX_ANYONE_CAN_PAY: u8 = 0x02;
+
+const SECP256K1_BLAKE160_CODE_HASH: [u8; 32] = [
+    0x9b, 0xd7, 0xe0, 0x6f, 0x3e, 0xcf, 0x4b, 0xe0, 0xf2, 0xfc, 0xd2, 0x18, 0x8b, 0x23, 0xf1, 0xb9,
+    0xfc, 0xc8, 0x8e, 0x5d, 0x4b, 0x65, 0xa8, 0x63, 0x7b, 0x17, 0x72, 0x3b, 0xbd, 0xa3, 0xcc, 0xe8,
+];
+const SECP256K1_MULTISIG_CODE_HASH: [u8; 32] = [
+    0x5c, 0x50, 0x69, 0xeb, 0x08, 0x57, 0xef, 0xc6, 0x5e, 0x1b, 0xca, 0x0c, 0x07, 0xdf, 0x34, 0xc3,
+    0x16, 0x63, 0xb3, 0x62, 0x2f, 0xd3, 0x87, 0x6c, 0x87, 0x63, 0x20, 0xfc, 0x96, 0x34, 0xe2, 0xa8,
+];
+const ANYONE_CAN_PAY_MAINNET_CODE_HASH: [u8; 32] = [
+    0xd3, 0x69, 0x59, 0x7f, 0xf4, 0x7f, 0x29, 0xfb, 0xc0, 0xd4, 0x7d, 0x2e, 0x37, 0x75, 0x37, 0x0d,
+    0x12, 0x50, 0xb8, 0x51, 0x40, 0xc6, 0x70, 0xe4, 0x71, 0x8a, 0xf7, 0x12, 0x98, 0x3a, 0x23, 0x54,
+];
+const ANYONE_CAN_PAY_TESTNET_CODE_HASH: [u8; 32] = [
+    0x34, 0x19, 0xa1, 0xc0, 0x9e, 0xb2, 0x56, 0x7f, 0x65, 0x52, 0xee, 0x7a, 0x8e, 0xcf, 0xfd, 0x64,
+    0x15, 0x5c, 0xff, 0xe0, 0xf1, 0x79, 0x6e, 0x6e, 0x61, 0xec, 0x08, 0x8d, 0x74, 0x0c, 0x13, 0x56,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => MAINNET_HRP,
+            Network::Testnet => TESTNET_HRP,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CkbAddressError {
+    Bech32(Bech32Error),
+    UnknownNetwork,
+    UnknownFormat,
+    /// The full format must be encoded with bech32m, deprecated formats with bech32
+    VariantMismatch,
+    InvalidPayload,
+    UnknownCodeHashIndex,
+}
+
+impl From<Bech32Error> for CkbAddressError {
+    fn from(err: Bech32Error) -> Self {
+        CkbAddressError::Bech32(err)
+    }
+}
+
+/// The lock script encoded in an address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressScript {
+    pub code_hash: [u8; 32],
+    pub hash_type: u8,
+    pub args: Vec<u8>,
+}
+
+impl AddressScript {
+    pub fn matches(&self, code_hash: &[u8], hash_type: u8, args: &[u8]) -> bool {
+        self.code_hash[..] == *code_hash && self.hash_type == hash_type && self.args[..] == *args
+    }
+
+    /// Encode in the full format
+    pub fn to_address(&self, network: Network) -> String {
+        let mut payload = Vec::with_capacity(1 + 32 + 1 + self.args.len());
+        payload.push(FULL_FORMAT);
+        payload.extend_from_slice(&self.code_hash);
+        payload.push(self.hash_type);
+        payload.extend_from_slice(&self.args);
+
+        // regrouping with padding never fails
+        let data = bech32::convert_bits(&payload, 8, 5, true).unwrap_or_default();
+        bech32::encode(network.hrp(), &data, Variant::Bech32m)
+    }
+}
+
+fn read_code_hash(payload: &[u8]) -> Result<[u8; 32], CkbAddressError> {
+    let mut code_hash = [0u8; 32];
+    if payload.len() < code_hash.len() {
+        return Err(CkbAddressError::InvalidPayload);
+    }
+    code_hash.copy_from_slice(&payload[..32]);
+    Ok(code_hash)
+}
+
+fn parse_short(network: Network, payload: &[u8]) -> Result<AddressScript, CkbAddressError> {
+    let (index, args) = payload
+        .split_first()
+        .ok_or(CkbAddressError::InvalidPayload)?;
+    let (code_hash, valid_args_len) = match *index {
+        CODE_HASH_INDEX_SECP256K1_BLAKE160 => (SECP256K1_BLAKE160_CODE_HASH, 20..=20),
+        CODE_HASH_INDEX_SECP256K1_MULTISIG => (SECP256K1_MULTISIG_CODE_HASH, 20..=20),
+        CODE_HASH_INDEX_ANYONE_CAN_PAY => match network {
+            Network::Mainnet => (ANYONE_CAN_PAY_MAINNET_CODE_HASH, 20..=22),
+            Network::Testnet => (ANYONE_CAN_PAY_TESTNET_CODE_HASH, 20..=22),
+        },
+        _ => return Err(CkbAddressError::UnknownCodeHashIndex),
+    };
+    if !valid_args_len.contains(&args.len()) {
+        return Err(CkbAddressError::InvalidPayload);
+    }
+
+    Ok(AddressScript {
+        code_hash,
+        hash_type: HASH_TYPE_TYPE,
+        args: args.to_vec(),
+    })
+}
+
+/// Decode an address in the full format or any of the deprecated formats
+pub fn parse(address: &str) -> Result<(Network, AddressScript), CkbAddressError> {
+    let (hrp, data, variant) = bech32::decode(address)?;
+    let network = match hrp.as_str() {
+        MAINNET_HRP => Network::Mainnet,
+        TESTNET_HRP => Network::Testnet,
+        _ => return Err(CkbAddressError::UnknownNetwork),
+    };
+    let payload = bech32::convert_bits(&data, 5, 8, false)?;
+    let (format, body) = payload
+        .split_first()
+        .ok_or(CkbAddressError::InvalidPayload)?;
+
+    let expected_variant = if *format == FULL_FORMAT {
+        Variant::Bech32m
+    } else {
+        Variant::Bech32
+    };
+    if variant != expected_variant {
+        return Err(CkbAddressError::VariantMismatch);
+    }
+
+    let script = match *format {
+        FULL_FORMAT => {
+            let code_hash = read_code_hash(body)?;
+            let hash_type = *body.get(32).ok_or(CkbAddressError::InvalidPayload)?;
+            if !matches!(
+                hash_type,
+                HASH_TYPE_DATA | HASH_TYPE_TYPE | HASH_TYPE_DATA1 | HASH_TYPE_DATA2
+            ) {
+                return Err(CkbAddressError::InvalidPayload);
+            }
+            AddressScript {
+                code_hash,
+                hash_type,
+                args: body[33..].to_vec(),
+            }
+        }
+        SHORT_FORMAT => parse_short(network, body)?,
+        FULL_DATA_FORMAT | FULL_TYPE_FORMAT => AddressScript {
+            code_hash: read_code_hash(body)?,
+            hash_type: if *format == FULL_DATA_FORMAT {
+                HASH_TYPE_DATA
+            } else {
+                HASH_TYPE_TYPE
+            },
+            args: body[32..].to_vec(),
+        },
+        _ => return Err(CkbAddressError::UnknownFormat),
+    };
+
+    Ok((network, script))
+}
@@ -0,0 +1,90 @@
+//! Ethereum addresses, 0x-prefixed 20 bytes hex with the EIP-55 mixed-case checksum.
+
+use crate::hash::keccak::keccak256;
+use alloc::string::String;
+
+pub const ADDRESS_LEN: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EthAddressError {
+    MissingPrefix,
+    InvalidLength,
+    InvalidChar,
+    InvalidChecksum,
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Whether the letter at `index` of the lowercase hex is uppercase in the checksummed form
+fn is_checksum_upper(hash: &[u8; 32], index: usize) -> bool {
+    let byte = hash[index / 2];
+    let nibble = if index & 1 == 0 { byte >> 4 } else { byte & 0x0f };
+    nibble >= 8
+}
+
+/// All lowercase or all uppercase addresses carry no checksum and are accepted as they are
+pub fn parse(address: &str) -> Result<[u8; ADDRESS_LEN], EthAddressError> {
+    let hex = address
+        .strip_prefix("0x")
+        .ok_or(EthAddressError::MissingPrefix)?
+        .as_bytes();
+    if hex.len() != ADDRESS_LEN * 2 {
+        return Err(EthAddressError::InvalidLength);
+    }
+
+    let mut bytes = [0u8; ADDRESS_LEN];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+        let high = hex_value(pair[0]).ok_or(EthAddressError::InvalidChar)?;
+        let low = hex_value(pair[1]).ok_or(EthAddressError::InvalidChar)?;
+        *byte = (high << 4) | low;
+    }
+
+    let has_lower = hex.iter().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.iter().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        let mut lowercase = [0u8; ADDRESS_LEN * 2];
+        for (lower, c) in lowercase.iter_mut().zip(hex.iter()) {
+            *lower = c.to_ascii_lowercase();
+        }
+        let hash = keccak256(&lowercase);
+        let valid = hex
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_ascii_alphabetic())
+            .all(|(index, c)| c.is_ascii_uppercase() == is_checksum_upper(&hash, index));
+        if !valid {
+            return Err(EthAddressError::InvalidChecksum);
+        }
+    }
+
+    Ok(bytes)
+}
+
+pub fn to_checksum_address(bytes: &[u8; ADDRESS_LEN]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut lowercase = [0u8; ADDRESS_LEN * 2];
+    for (pair, byte) in lowercase.chunks_exact_mut(2).zip(bytes.iter()) {
+        pair[0] = DIGITS[(byte >> 4) as usize];
+        pair[1] = DIGITS[(byte & 0x0f) as usize];
+    }
+    let hash = keccak256(&lowercase);
+
+    let mut address = String::with_capacity(2 + ADDRESS_LEN * 2);
+    address.push_str("0x");
+    for (index, c) in lowercase.iter().enumerate() {
+        if c.is_ascii_alphabetic() && is_checksum_upper(&hash, index) {
+            address.push(c.to_ascii_uppercase() as char);
+        } else {
+            address.push(*c as char);
+        }
+    }
+    address
+}
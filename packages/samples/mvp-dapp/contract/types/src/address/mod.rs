@@ -0,0 +1,28 @@
+pub mod base58;
+pub mod bech32;
+pub mod btc;
+pub mod ckb;
+pub mod eth;
+
+pub const KEY_BTC: &str = "btc";
+pub const KEY_ETH: &str = "eth";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainAddressError {
+    Btc(btc::BtcAddressError),
+    Eth(eth::EthAddressError),
+}
+
+/// Whether `verify_entry` checks the values of entries keyed by `key`
+pub fn has_format(key: &str) -> bool {
+    matches!(key, KEY_BTC | KEY_ETH)
+}
+
+/// Check the value of an entry when its key names a chain with a known address format
+pub fn verify_entry(key: &str, value: &str) -> Result<(), ChainAddressError> {
+    match key {
+        KEY_BTC => btc::parse(value).map(|_| ()).map_err(ChainAddressError::Btc),
+        KEY_ETH => eth::parse(value).map(|_| ()).map_err(ChainAddressError::Eth),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,144 @@
+//! Canonical JSON in the style of RFC 8785 "JSON Canonicalization Scheme".
+//!
+//! Object members are sorted by the UTF-16 code units of their names, there is no whitespace
+//! between tokens, strings only escape what JSON requires and numbers are written as
+//! ECMAScript writes doubles.
+//!
+//! A record is canonical when it is `to_string` of its own view: sections and entry fields
+//! ordered by name, `{"key":…,"label":…,"value":…}`, with every label written and empty
+//! sections left out. `is_canonical` checks exactly this with `json_stream::Validator`, the
+//! same way whether the record is read as a whole or in windows. `JSON.stringify` writes
+//! strings the same way, and `RecordModel.update` orders the members of a record with
+//! `canonicalRecord` of `src/actors/record.model.ts`, so the records it writes are canonical.
+
+use crate::json_stream::Validator;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanonicalJsonError {
+    InvalidJson,
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// The shortest digits reading back as the same double, laid out like
+/// `Number.prototype.toString`, see RFC 8785 section 3.2.2.3
+fn write_number(out: &mut String, number: &Number) {
+    let value = number.as_f64().unwrap_or_default();
+    let abs = match Number::from_f64(value.abs()) {
+        Some(abs) if value != 0.0 => abs,
+        _ => {
+            out.push('0');
+            return;
+        }
+    };
+
+    // `serde_json` writes doubles with `ryu`, such as `100.0`, `1.5e-7` or `1e21`
+    let mut repr = String::new();
+    let _ = write!(repr, "{}", abs);
+    let (mantissa, exponent) = match repr.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().unwrap_or_default()),
+        None => (repr.as_str(), 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: String = int.chars().chain(frac.chars()).collect();
+    let leading = digits.len() - digits.trim_start_matches('0').len();
+    let digits = digits[leading..].trim_end_matches('0');
+    // the value is `0.digits` times `10^point`
+    let point = int.len() as i32 + exponent - leading as i32;
+    let len = digits.len() as i32;
+
+    if value < 0.0 {
+        out.push('-');
+    }
+    if len <= point && point <= 21 {
+        out.push_str(digits);
+        out.extend((len..point).map(|_| '0'));
+    } else if 0 < point && point <= 21 {
+        let (int, frac) = digits.split_at(point as usize);
+        let _ = write!(out, "{}.{}", int, frac);
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.extend((point..0).map(|_| '0'));
+        out.push_str(digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            let _ = write!(out, ".{}", rest);
+        }
+        let sign = if point > 0 { '+' } else { '-' };
+        let _ = write!(out, "e{}{}", sign, (point - 1).abs());
+    }
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(true) => out.push_str("true"),
+        Value::Bool(false) => out.push_str("false"),
+        Value::Number(number) => write_number(out, number),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<(&String, &Value)> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (index, (name, member)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_string(out, name);
+                out.push(':');
+                write_value(out, member);
+            }
+            out.push('}');
+        }
+    }
+}
+
+pub fn to_string<T: Serialize>(value: &T) -> Result<String, CanonicalJsonError> {
+    let value = serde_json::to_value(value).map_err(|_| CanonicalJsonError::InvalidJson)?;
+    let mut out = String::new();
+    write_value(&mut out, &value);
+    Ok(out)
+}
+
+/// Whether `data` is a record in canonical form, see the module documentation
+pub fn is_canonical(data: &[u8]) -> bool {
+    let mut validator = Validator::new();
+    validator.feed(data, |_, _| {});
+    validator.is_canonical() && validator.finish().is_ok()
+}
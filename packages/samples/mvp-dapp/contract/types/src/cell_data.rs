@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+/// Every record cell data starts with this prefix, see `DAPP_DATA_PREFIX` in `src/const.ts`
+pub const DAPP_DATA_PREFIX: &[u8] = b"mvp-dapp";
+pub const DAPP_DATA_PREFIX_LEN: usize = DAPP_DATA_PREFIX.len();
+
+/// Tags of the byte following the prefix
+pub const FORMAT_JSON: u8 = 0x00;
+pub const FORMAT_MOLECULE: u8 = 0x01;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Molecule,
+}
+
+impl DataFormat {
+    pub fn tag(&self) -> u8 {
+        match self {
+            DataFormat::Json => FORMAT_JSON,
+            DataFormat::Molecule => FORMAT_MOLECULE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellDataError {
+    /// The data is shorter than the prefix
+    TooShort,
+    /// The data doesn't start with `DAPP_DATA_PREFIX`
+    PrefixMismatch,
+    /// Nothing follows the prefix
+    MissingFormat,
+    UnknownFormat,
+}
+
+/// Check the header of the cell data and return the record part behind the prefix
+pub fn strip_prefix(data: &[u8]) -> Result<&[u8], CellDataError> {
+    if data.len() < DAPP_DATA_PREFIX_LEN {
+        return Err(CellDataError::TooShort);
+    }
+
+    let (prefix, body) = data.split_at(DAPP_DATA_PREFIX_LEN);
+    if prefix != DAPP_DATA_PREFIX {
+        return Err(CellDataError::PrefixMismatch);
+    }
+
+    Ok(body)
+}
+
+/// Read the format of the record behind the prefix.
+///
+/// Records written by `JSONStorage` have no format tag and start with `{` directly.
+pub fn split_format(body: &[u8]) -> Result<(DataFormat, &[u8]), CellDataError> {
+    match body.split_first() {
+        None => Err(CellDataError::MissingFormat),
+        Some((&b'{', _)) => Ok((DataFormat::Json, body)),
+        Some((&FORMAT_JSON, record)) => Ok((DataFormat::Json, record)),
+        Some((&FORMAT_MOLECULE, record)) => Ok((DataFormat::Molecule, record)),
+        Some(_) => Err(CellDataError::UnknownFormat),
+    }
+}
+
+/// Prepend `DAPP_DATA_PREFIX` to a serialized record
+pub fn with_prefix(body: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(DAPP_DATA_PREFIX_LEN + body.len());
+    data.extend_from_slice(DAPP_DATA_PREFIX);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Prepend `DAPP_DATA_PREFIX` and the format tag to a serialized record
+pub fn with_format(format: DataFormat, record: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(DAPP_DATA_PREFIX_LEN + 1 + record.len());
+    data.extend_from_slice(DAPP_DATA_PREFIX);
+    data.push(format.tag());
+    data.extend_from_slice(record);
+    data
+}
@@ -0,0 +1,278 @@
+//! Limits of a deployment, read from the args of the type script.
+//!
+//! Empty args keep the contract unrestricted, otherwise the args must be a `MvpConfig` in molecule.
+//! A limit set to 0 is not enforced.
+//!
+//! With `FLAG_TYPE_ID` the config is followed by the identifier of the record, so every record
+//! has its own type script and lives in a script group of its own.
+
+use crate::generated::mvp_config::{
+    MvpConfig, MvpConfigReader, Uint32, Uint32Reader, Uint64, Uint64Reader,
+};
+use crate::hash::blake2b::{Blake2b, CKB_PERSONALIZATION};
+use crate::kuai_mvp_data::KuaiMvpView;
+use crate::record_reader::RecordReader;
+use molecule::{
+    bytes::Bytes,
+    prelude::{Builder, Byte, Entity, Reader},
+};
+
+/// Bits of `allowed_sections`, in the order of `KuaiMvpView::sections`
+pub const SECTION_PROFILE: u8 = 1;
+pub const SECTION_ADDRESSES: u8 = 1 << 1;
+pub const SECTION_CUSTOM: u8 = 1 << 2;
+pub const SECTION_DWEB: u8 = 1 << 3;
+pub const SECTION_ALL: u8 = SECTION_PROFILE | SECTION_ADDRESSES | SECTION_CUSTOM | SECTION_DWEB;
+
+/// Reject molecule records with fields unknown to this contract
+pub const FLAG_STRICT_MOLECULE: u8 = 1;
+/// Reject records written without a format tag behind the prefix
+pub const FLAG_REQUIRE_FORMAT_TAG: u8 = 1 << 1;
+/// Records are singletons identified like type id cells, see `parse_args`
+pub const FLAG_TYPE_ID: u8 = 1 << 2;
+/// Records can't be destroyed once created
+pub const FLAG_IMMUTABLE: u8 = 1 << 3;
+/// Entries of every section must be sorted by key, see `KuaiMvpView::keys_sorted`
+pub const FLAG_SORTED_KEYS: u8 = 1 << 4;
+/// JSON records must be in the form of `KuaiMvpView::as_canonical_json`, see `canonical_json`
+pub const FLAG_CANONICAL_JSON: u8 = 1 << 5;
+
+pub const TYPE_ID_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidArgs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitError {
+    TooManyEntries,
+    KeyTooLong,
+    ValueTooLong,
+    DataTooLarge,
+    SectionNotAllowed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub max_entries: u32,
+    pub max_key_length: u32,
+    pub max_value_length: u32,
+    pub max_data_size: u32,
+    pub allowed_sections: u8,
+    pub flags: u8,
+    /// Since value a record input must declare to be destroyed, 0 to allow destruction at any time
+    pub lock_in_period: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_entries: 0,
+            max_key_length: 0,
+            max_value_length: 0,
+            max_data_size: 0,
+            allowed_sections: SECTION_ALL,
+            flags: 0,
+            lock_in_period: 0,
+        }
+    }
+}
+
+fn as_uint32(n: u32) -> Uint32 {
+    let bytes = n.to_le_bytes();
+    Uint32::new_builder()
+        .set([
+            Byte::new(bytes[0]),
+            Byte::new(bytes[1]),
+            Byte::new(bytes[2]),
+            Byte::new(bytes[3]),
+        ])
+        .build()
+}
+
+fn from_uint32(n: Uint32Reader) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(n.raw_data());
+    u32::from_le_bytes(bytes)
+}
+
+fn as_uint64(n: u64) -> Uint64 {
+    let bytes = n.to_le_bytes();
+    Uint64::new_builder()
+        .set([
+            Byte::new(bytes[0]),
+            Byte::new(bytes[1]),
+            Byte::new(bytes[2]),
+            Byte::new(bytes[3]),
+            Byte::new(bytes[4]),
+            Byte::new(bytes[5]),
+            Byte::new(bytes[6]),
+            Byte::new(bytes[7]),
+        ])
+        .build()
+}
+
+fn from_uint64(n: Uint64Reader) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(n.raw_data());
+    u64::from_le_bytes(bytes)
+}
+
+fn exceeds(len: usize, limit: u32) -> bool {
+    limit != 0 && len > limit as usize
+}
+
+/// Identifier of the record created at `output_index` by a transaction whose first input is
+/// `first_input`, the args a type id cell would get
+pub fn type_id(first_input: &[u8], output_index: u64) -> [u8; TYPE_ID_LEN] {
+    let mut hasher = Blake2b::new(CKB_PERSONALIZATION);
+    hasher.update(first_input);
+    hasher.update(&output_index.to_le_bytes());
+    hasher.finalize()
+}
+
+/// Split the script args into the config and, with `FLAG_TYPE_ID`, the identifier of the record
+pub fn parse_args(args: &[u8]) -> Result<(Config, Option<[u8; TYPE_ID_LEN]>), ConfigError> {
+    if args.is_empty() {
+        return Ok((Config::default(), None));
+    }
+    if args.len() < molecule::NUMBER_SIZE {
+        return Err(ConfigError::InvalidArgs);
+    }
+
+    let config_len = molecule::unpack_number(args) as usize;
+    if args.len() < config_len {
+        return Err(ConfigError::InvalidArgs);
+    }
+    let (config_args, rest) = args.split_at(config_len);
+    let config = Config::from_args(config_args)?;
+
+    if !config.has_flag(FLAG_TYPE_ID) {
+        if !rest.is_empty() {
+            return Err(ConfigError::InvalidArgs);
+        }
+        return Ok((config, None));
+    }
+
+    if rest.len() != TYPE_ID_LEN {
+        return Err(ConfigError::InvalidArgs);
+    }
+    let mut type_id = [0u8; TYPE_ID_LEN];
+    type_id.copy_from_slice(rest);
+
+    Ok((config, Some(type_id)))
+}
+
+impl Config {
+    /// Extra fields are accepted so args written for a newer contract still load
+    pub fn from_args(args: &[u8]) -> Result<Config, ConfigError> {
+        if args.is_empty() {
+            return Ok(Config::default());
+        }
+
+        MvpConfigReader::verify(args, true).map_err(|_| ConfigError::InvalidArgs)?;
+        let reader = MvpConfigReader::new_unchecked(args);
+
+        Ok(Config {
+            max_entries: from_uint32(reader.max_entries()),
+            max_key_length: from_uint32(reader.max_key_length()),
+            max_value_length: from_uint32(reader.max_value_length()),
+            max_data_size: from_uint32(reader.max_data_size()),
+            allowed_sections: reader.allowed_sections().as_slice()[0],
+            flags: reader.flags().as_slice()[0],
+            lock_in_period: from_uint64(reader.lock_in_period()),
+        })
+    }
+
+    pub fn as_args(&self) -> Bytes {
+        MvpConfig::new_builder()
+            .max_entries(as_uint32(self.max_entries))
+            .max_key_length(as_uint32(self.max_key_length))
+            .max_value_length(as_uint32(self.max_value_length))
+            .max_data_size(as_uint32(self.max_data_size))
+            .allowed_sections(Byte::new(self.allowed_sections))
+            .flags(Byte::new(self.flags))
+            .lock_in_period(as_uint64(self.lock_in_period))
+            .build()
+            .as_bytes()
+    }
+
+    /// Args of a record of a `FLAG_TYPE_ID` deployment
+    pub fn as_type_id_args(&self, type_id: &[u8; TYPE_ID_LEN]) -> Bytes {
+        let mut args = self.as_args().to_vec();
+        args.extend_from_slice(type_id);
+        args.into()
+    }
+
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+
+    /// `len` is the size of the whole cell data, prefix included
+    pub fn check_data_size(&self, len: usize) -> Result<(), LimitError> {
+        if exceeds(len, self.max_data_size) {
+            return Err(LimitError::DataTooLarge);
+        }
+
+        Ok(())
+    }
+
+    pub fn check_view(&self, view: &KuaiMvpView) -> Result<(), LimitError> {
+        for (index, (_, items)) in view.sections().iter().enumerate() {
+            let lengths = items.iter().map(|item| (item.key.len(), item.value.len()));
+            self.check_section(index, items.len(), lengths)?;
+        }
+
+        Ok(())
+    }
+
+    /// Same as `check_view` over the entries of a molecule record
+    pub fn check_reader(&self, record: &RecordReader) -> Result<(), LimitError> {
+        for (index, (_, entries)) in record.sections().into_iter().enumerate() {
+            let count = entries.len();
+            let lengths = entries.map(|entry| (entry.key.len(), entry.value.len()));
+            self.check_section(index, count, lengths)?;
+        }
+
+        Ok(())
+    }
+
+    /// Same as `check_view` for a single entry, the `index`th of the section at `section`,
+    /// when the record is read as a stream
+    pub fn check_entry(
+        &self,
+        section: usize,
+        index: usize,
+        key_len: usize,
+        value_len: usize,
+    ) -> Result<(), LimitError> {
+        self.check_section(section, index + 1, core::iter::once((key_len, value_len)))
+    }
+
+    /// `lengths` are the key and value lengths of the `count` entries of the section at `index`
+    fn check_section<I>(&self, index: usize, count: usize, lengths: I) -> Result<(), LimitError>
+    where
+        I: Iterator<Item = (usize, usize)>,
+    {
+        if count == 0 {
+            return Ok(());
+        }
+        if self.allowed_sections & (1 << index) == 0 {
+            return Err(LimitError::SectionNotAllowed);
+        }
+        if exceeds(count, self.max_entries) {
+            return Err(LimitError::TooManyEntries);
+        }
+        for (key_len, value_len) in lengths {
+            if exceeds(key_len, self.max_key_length) {
+                return Err(LimitError::KeyTooLong);
+            }
+            if exceeds(value_len, self.max_value_length) {
+                return Err(LimitError::ValueTooLong);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//! Changes between two records, in the order they are listed by `diff`.
+//!
+//! Entries are matched by key within their section. A re-keyed entry is a removed entry and an
+//! added one.
+
+use crate::json_record::Section;
+use crate::kuai_mvp_data::{Data, KuaiMvpView};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Change {
+    Added {
+        section: Section,
+        entry: Data,
+    },
+    Removed {
+        section: Section,
+        entry: Data,
+    },
+    ValueChanged {
+        section: Section,
+        key: String,
+        old: String,
+        new: String,
+    },
+    LabelChanged {
+        section: Section,
+        key: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl Change {
+    pub fn section(&self) -> Section {
+        match self {
+            Change::Added { section, .. }
+            | Change::Removed { section, .. }
+            | Change::ValueChanged { section, .. }
+            | Change::LabelChanged { section, .. } => *section,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            Change::Added { entry, .. } | Change::Removed { entry, .. } => &entry.key,
+            Change::ValueChanged { key, .. } | Change::LabelChanged { key, .. } => key,
+        }
+    }
+}
+
+fn find<'a>(items: &'a [Data], key: &str) -> Option<&'a Data> {
+    items.iter().find(|item| item.key == key)
+}
+
+/// Sections in the order of `KuaiMvpView::sections`. In each section the removed entries come
+/// first in their old order, then the added and changed entries in their new order, with the
+/// value of an entry before its label
+pub fn diff(old: &KuaiMvpView, new: &KuaiMvpView) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let sections = old.sections().into_iter().zip(new.sections());
+    for (section, ((_, old_items), (_, new_items))) in Section::ALL.into_iter().zip(sections) {
+        for item in old_items {
+            if find(new_items, &item.key).is_none() {
+                changes.push(Change::Removed {
+                    section,
+                    entry: item.clone(),
+                });
+            }
+        }
+
+        for item in new_items {
+            let old_item = match find(old_items, &item.key) {
+                Some(old_item) => old_item,
+                None => {
+                    changes.push(Change::Added {
+                        section,
+                        entry: item.clone(),
+                    });
+                    continue;
+                }
+            };
+            if old_item.value != item.value {
+                changes.push(Change::ValueChanged {
+                    section,
+                    key: item.key.clone(),
+                    old: old_item.value.clone(),
+                    new: item.value.clone(),
+                });
+            }
+            if old_item.label != item.label {
+                changes.push(Change::LabelChanged {
+                    section,
+                    key: item.key.clone(),
+                    old: old_item.label.clone(),
+                    new: item.label.clone(),
+                });
+            }
+        }
+    }
+    changes
+}
@@ -0,0 +1,186 @@
+//! Content identifiers of the `dweb` section: IPFS CIDs, IPNS keys, Arweave ids and Swarm
+//! references.
+
+use crate::address::base58;
+use alloc::vec::Vec;
+
+pub const KEY_IPFS: &str = "ipfs";
+pub const KEY_IPNS: &str = "ipns";
+pub const KEY_ARWEAVE: &str = "arweave";
+pub const KEY_SWARM: &str = "swarm";
+
+const BASE16: &[u8] = b"0123456789abcdef";
+const BASE32: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE58_BTC: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+const CID_V0_LEN: usize = 46;
+const CID_V1: u64 = 1;
+const CODEC_LIBP2P_KEY: u64 = 0x72;
+const MULTIHASH_IDENTITY: u64 = 0x00;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+const SHA2_256_LEN: usize = 32;
+
+const ARWEAVE_ID_LEN: usize = 43;
+const SWARM_REFERENCE_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DwebError {
+    /// A character outside of the alphabet of the encoding
+    InvalidEncoding,
+    UnsupportedMultibase,
+    InvalidCid,
+    InvalidMultihash,
+    /// An IPNS name that is not a libp2p key
+    InvalidKey,
+    InvalidLength,
+}
+
+/// Unpack characters of `bits` bits each, the bits left over must be zero
+fn decode_bits(s: &str, alphabet: &[u8], bits: u32) -> Result<Vec<u8>, DwebError> {
+    let mut bytes = Vec::with_capacity(s.len() * bits as usize / 8);
+    let mut acc: u32 = 0;
+    let mut acc_bits: u32 = 0;
+    for c in s.bytes() {
+        let value = alphabet
+            .iter()
+            .position(|a| *a == c)
+            .ok_or(DwebError::InvalidEncoding)? as u32;
+        acc = (acc << bits) | value;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            bytes.push((acc >> acc_bits) as u8);
+            acc &= (1 << acc_bits) - 1;
+        }
+    }
+
+    if acc != 0 || acc_bits >= bits {
+        return Err(DwebError::InvalidEncoding);
+    }
+    Ok(bytes)
+}
+
+fn decode_number(s: &str, alphabet: &[u8]) -> Result<Vec<u8>, DwebError> {
+    base58::decode_alphabet(s, alphabet).map_err(|_| DwebError::InvalidEncoding)
+}
+
+fn decode_multibase(s: &str) -> Result<Vec<u8>, DwebError> {
+    let mut chars = s.chars();
+    let prefix = chars.next().ok_or(DwebError::InvalidLength)?;
+    let rest = chars.as_str();
+    let lowercase = || rest.to_ascii_lowercase();
+
+    match prefix {
+        'f' => decode_bits(rest, BASE16, 4),
+        'F' => decode_bits(&lowercase(), BASE16, 4),
+        'b' => decode_bits(rest, BASE32, 5),
+        'B' => decode_bits(&lowercase(), BASE32, 5),
+        'k' => decode_number(rest, BASE36),
+        'K' => decode_number(&lowercase(), BASE36),
+        'z' => decode_number(rest, BASE58_BTC),
+        'm' => decode_bits(rest, BASE64, 6),
+        'u' => decode_bits(rest, BASE64_URL, 6),
+        _ => Err(DwebError::UnsupportedMultibase),
+    }
+}
+
+/// Read an unsigned LEB128 varint of at most 9 bytes, as multiformats limits them
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value: u64 = 0;
+    for (index, byte) in bytes.iter().enumerate().take(9) {
+        value |= ((byte & 0x7f) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[index + 1..]));
+        }
+    }
+    None
+}
+
+/// The multihash must fill `bytes` exactly, returns the code of the hash function
+fn verify_multihash(bytes: &[u8]) -> Result<u64, DwebError> {
+    let (code, rest) = read_varint(bytes).ok_or(DwebError::InvalidMultihash)?;
+    let (len, digest) = read_varint(rest).ok_or(DwebError::InvalidMultihash)?;
+    if digest.len() as u64 != len {
+        return Err(DwebError::InvalidMultihash);
+    }
+    if code == MULTIHASH_SHA2_256 && digest.len() != SHA2_256_LEN {
+        return Err(DwebError::InvalidMultihash);
+    }
+    Ok(code)
+}
+
+/// Returns the content codec of a CIDv1, CIDv0 always holds dag-pb
+fn parse_cid_v1(s: &str) -> Result<u64, DwebError> {
+    let bytes = decode_multibase(s)?;
+    let (version, rest) = read_varint(&bytes).ok_or(DwebError::InvalidCid)?;
+    if version != CID_V1 {
+        return Err(DwebError::InvalidCid);
+    }
+    let (codec, multihash) = read_varint(rest).ok_or(DwebError::InvalidCid)?;
+    verify_multihash(multihash)?;
+    Ok(codec)
+}
+
+/// CIDv0 is a base58btc sha2-256 multihash, anything else is a multibase CIDv1
+pub fn parse_ipfs(s: &str) -> Result<(), DwebError> {
+    if s.len() == CID_V0_LEN && s.starts_with("Qm") {
+        let bytes = base58::decode(s).map_err(|_| DwebError::InvalidEncoding)?;
+        if verify_multihash(&bytes)? != MULTIHASH_SHA2_256 {
+            return Err(DwebError::InvalidMultihash);
+        }
+        return Ok(());
+    }
+
+    parse_cid_v1(s).map(|_| ())
+}
+
+/// A peer id in base58btc, e.g. `12D3KooW…` or `Qm…`, or a CIDv1 with the libp2p-key codec
+pub fn parse_ipns(s: &str) -> Result<(), DwebError> {
+    if s.starts_with('1') || s.starts_with("Qm") {
+        let bytes = base58::decode(s).map_err(|_| DwebError::InvalidEncoding)?;
+        return match verify_multihash(&bytes)? {
+            MULTIHASH_IDENTITY | MULTIHASH_SHA2_256 => Ok(()),
+            _ => Err(DwebError::InvalidKey),
+        };
+    }
+
+    if parse_cid_v1(s)? != CODEC_LIBP2P_KEY {
+        return Err(DwebError::InvalidKey);
+    }
+    Ok(())
+}
+
+/// Transaction ids are 32 bytes in unpadded base64url
+pub fn parse_arweave(s: &str) -> Result<(), DwebError> {
+    if s.len() != ARWEAVE_ID_LEN {
+        return Err(DwebError::InvalidLength);
+    }
+    decode_bits(s, BASE64_URL, 6).map(|_| ())
+}
+
+/// References are 32 bytes in hex
+pub fn parse_swarm(s: &str) -> Result<(), DwebError> {
+    if s.len() != SWARM_REFERENCE_LEN * 2 {
+        return Err(DwebError::InvalidLength);
+    }
+    decode_bits(&s.to_ascii_lowercase(), BASE16, 4).map(|_| ())
+}
+
+/// Whether `verify_entry` checks the values of `dweb` entries keyed by `key`
+pub fn has_format(key: &str) -> bool {
+    matches!(key, KEY_IPFS | KEY_IPNS | KEY_ARWEAVE | KEY_SWARM)
+}
+
+/// Check the value of a `dweb` entry when its key names a known network
+pub fn verify_entry(key: &str, value: &str) -> Result<(), DwebError> {
+    match key {
+        KEY_IPFS => parse_ipfs(value),
+        KEY_IPNS => parse_ipns(value),
+        KEY_ARWEAVE => parse_arweave(value),
+        KEY_SWARM => parse_swarm(value),
+        _ => Ok(()),
+    }
+}
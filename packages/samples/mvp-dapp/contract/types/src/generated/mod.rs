@@ -2,3 +2,5 @@
 pub mod basic;
 #[allow(clippy::all)]
 pub mod mvp_data;
+#[allow(clippy::all)]
+pub mod mvp_config;
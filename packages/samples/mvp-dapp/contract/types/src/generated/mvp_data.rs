@@ -23,6 +23,7 @@ impl ::core::fmt::Display for Obj {
         write!(f, "{} {{ ", Self::NAME)?;
         write!(f, "{}: {}", "key", self.key())?;
         write!(f, ", {}: {}", "value", self.value())?;
+        write!(f, ", {}: {}", "label", self.label())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -33,13 +34,13 @@ impl ::core::fmt::Display for Obj {
 impl ::core::default::Default for Obj {
     fn default() -> Self {
         let v: Vec<u8> = vec![
-            20, 0, 0, 0, 12, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            28, 0, 0, 0, 16, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         ];
         Obj::new_unchecked(v.into())
     }
 }
 impl Obj {
-    pub const FIELD_COUNT: usize = 2;
+    pub const FIELD_COUNT: usize = 3;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -65,8 +66,14 @@ impl Obj {
     pub fn value(&self) -> MString {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        MString::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn label(&self) -> MString {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[12..]) as usize;
+            let end = molecule::unpack_number(&slice[16..]) as usize;
             MString::new_unchecked(self.0.slice(start..end))
         } else {
             MString::new_unchecked(self.0.slice(start..))
@@ -98,7 +105,10 @@ impl molecule::prelude::Entity for Obj {
         ::core::default::Default::default()
     }
     fn as_builder(self) -> Self::Builder {
-        Self::new_builder().key(self.key()).value(self.value())
+        Self::new_builder()
+            .key(self.key())
+            .value(self.value())
+            .label(self.label())
     }
 }
 #[derive(Clone, Copy)]
@@ -122,6 +132,7 @@ impl<'r> ::core::fmt::Display for ObjReader<'r> {
         write!(f, "{} {{ ", Self::NAME)?;
         write!(f, "{}: {}", "key", self.key())?;
         write!(f, ", {}: {}", "value", self.value())?;
+        write!(f, ", {}: {}", "label", self.label())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -130,7 +141,7 @@ impl<'r> ::core::fmt::Display for ObjReader<'r> {
     }
 }
 impl<'r> ObjReader<'r> {
-    pub const FIELD_COUNT: usize = 2;
+    pub const FIELD_COUNT: usize = 3;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -156,8 +167,14 @@ impl<'r> ObjReader<'r> {
     pub fn value(&self) -> MStringReader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        MStringReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn label(&self) -> MStringReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[12..]) as usize;
+            let end = molecule::unpack_number(&slice[16..]) as usize;
             MStringReader::new_unchecked(&self.as_slice()[start..end])
         } else {
             MStringReader::new_unchecked(&self.as_slice()[start..])
@@ -215,6 +232,7 @@ impl<'r> molecule::prelude::Reader<'r> for ObjReader<'r> {
         }
         MStringReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
         MStringReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        MStringReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
         Ok(())
     }
 }
@@ -222,9 +240,10 @@ impl<'r> molecule::prelude::Reader<'r> for ObjReader<'r> {
 pub struct ObjBuilder {
     pub(crate) key: MString,
     pub(crate) value: MString,
+    pub(crate) label: MString,
 }
 impl ObjBuilder {
-    pub const FIELD_COUNT: usize = 2;
+    pub const FIELD_COUNT: usize = 3;
     pub fn key(mut self, v: MString) -> Self {
         self.key = v;
         self
@@ -233,6 +252,10 @@ impl ObjBuilder {
         self.value = v;
         self
     }
+    pub fn label(mut self, v: MString) -> Self {
+        self.label = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for ObjBuilder {
     type Entity = Obj;
@@ -241,6 +264,7 @@ impl molecule::prelude::Builder for ObjBuilder {
         molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
             + self.key.as_slice().len()
             + self.value.as_slice().len()
+            + self.label.as_slice().len()
     }
     fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
@@ -249,12 +273,15 @@ impl molecule::prelude::Builder for ObjBuilder {
         total_size += self.key.as_slice().len();
         offsets.push(total_size);
         total_size += self.value.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.label.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
         }
         writer.write_all(self.key.as_slice())?;
         writer.write_all(self.value.as_slice())?;
+        writer.write_all(self.label.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -622,7 +649,10 @@ impl ::core::fmt::Debug for KuaiMvpData {
 impl ::core::fmt::Display for KuaiMvpData {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} {{ ", Self::NAME)?;
-        write!(f, "{}: {}", "addresses", self.addresses())?;
+        write!(f, "{}: {}", "profile", self.profile())?;
+        write!(f, ", {}: {}", "addresses", self.addresses())?;
+        write!(f, ", {}: {}", "custom", self.custom())?;
+        write!(f, ", {}: {}", "dweb", self.dweb())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -632,12 +662,15 @@ impl ::core::fmt::Display for KuaiMvpData {
 }
 impl ::core::default::Default for KuaiMvpData {
     fn default() -> Self {
-        let v: Vec<u8> = vec![12, 0, 0, 0, 8, 0, 0, 0, 4, 0, 0, 0];
+        let v: Vec<u8> = vec![
+            36, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 28, 0, 0, 0, 32, 0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0,
+            4, 0, 0, 0, 4, 0, 0, 0,
+        ];
         KuaiMvpData::new_unchecked(v.into())
     }
 }
 impl KuaiMvpData {
-    pub const FIELD_COUNT: usize = 1;
+    pub const FIELD_COUNT: usize = 4;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -654,11 +687,29 @@ impl KuaiMvpData {
     pub fn has_extra_fields(&self) -> bool {
         Self::FIELD_COUNT != self.field_count()
     }
-    pub fn addresses(&self) -> Objs {
+    pub fn profile(&self) -> Objs {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Objs::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn addresses(&self) -> Objs {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Objs::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn custom(&self) -> Objs {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Objs::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn dweb(&self) -> Objs {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[8..]) as usize;
+            let end = molecule::unpack_number(&slice[20..]) as usize;
             Objs::new_unchecked(self.0.slice(start..end))
         } else {
             Objs::new_unchecked(self.0.slice(start..))
@@ -690,7 +741,11 @@ impl molecule::prelude::Entity for KuaiMvpData {
         ::core::default::Default::default()
     }
     fn as_builder(self) -> Self::Builder {
-        Self::new_builder().addresses(self.addresses())
+        Self::new_builder()
+            .profile(self.profile())
+            .addresses(self.addresses())
+            .custom(self.custom())
+            .dweb(self.dweb())
     }
 }
 #[derive(Clone, Copy)]
@@ -712,7 +767,10 @@ impl<'r> ::core::fmt::Debug for KuaiMvpDataReader<'r> {
 impl<'r> ::core::fmt::Display for KuaiMvpDataReader<'r> {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         write!(f, "{} {{ ", Self::NAME)?;
-        write!(f, "{}: {}", "addresses", self.addresses())?;
+        write!(f, "{}: {}", "profile", self.profile())?;
+        write!(f, ", {}: {}", "addresses", self.addresses())?;
+        write!(f, ", {}: {}", "custom", self.custom())?;
+        write!(f, ", {}: {}", "dweb", self.dweb())?;
         let extra_count = self.count_extra_fields();
         if extra_count != 0 {
             write!(f, ", .. ({} fields)", extra_count)?;
@@ -721,7 +779,7 @@ impl<'r> ::core::fmt::Display for KuaiMvpDataReader<'r> {
     }
 }
 impl<'r> KuaiMvpDataReader<'r> {
-    pub const FIELD_COUNT: usize = 1;
+    pub const FIELD_COUNT: usize = 4;
     pub fn total_size(&self) -> usize {
         molecule::unpack_number(self.as_slice()) as usize
     }
@@ -738,11 +796,29 @@ impl<'r> KuaiMvpDataReader<'r> {
     pub fn has_extra_fields(&self) -> bool {
         Self::FIELD_COUNT != self.field_count()
     }
-    pub fn addresses(&self) -> ObjsReader<'r> {
+    pub fn profile(&self) -> ObjsReader<'r> {
         let slice = self.as_slice();
         let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        ObjsReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn addresses(&self) -> ObjsReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        ObjsReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn custom(&self) -> ObjsReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        ObjsReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn dweb(&self) -> ObjsReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
         if self.has_extra_fields() {
-            let end = molecule::unpack_number(&slice[8..]) as usize;
+            let end = molecule::unpack_number(&slice[20..]) as usize;
             ObjsReader::new_unchecked(&self.as_slice()[start..end])
         } else {
             ObjsReader::new_unchecked(&self.as_slice()[start..])
@@ -799,36 +875,67 @@ impl<'r> molecule::prelude::Reader<'r> for KuaiMvpDataReader<'r> {
             return ve!(Self, OffsetsNotMatch);
         }
         ObjsReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        ObjsReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        ObjsReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        ObjsReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
         Ok(())
     }
 }
 #[derive(Debug, Default)]
 pub struct KuaiMvpDataBuilder {
+    pub(crate) profile: Objs,
     pub(crate) addresses: Objs,
+    pub(crate) custom: Objs,
+    pub(crate) dweb: Objs,
 }
 impl KuaiMvpDataBuilder {
-    pub const FIELD_COUNT: usize = 1;
+    pub const FIELD_COUNT: usize = 4;
+    pub fn profile(mut self, v: Objs) -> Self {
+        self.profile = v;
+        self
+    }
     pub fn addresses(mut self, v: Objs) -> Self {
         self.addresses = v;
         self
     }
+    pub fn custom(mut self, v: Objs) -> Self {
+        self.custom = v;
+        self
+    }
+    pub fn dweb(mut self, v: Objs) -> Self {
+        self.dweb = v;
+        self
+    }
 }
 impl molecule::prelude::Builder for KuaiMvpDataBuilder {
     type Entity = KuaiMvpData;
     const NAME: &'static str = "KuaiMvpDataBuilder";
     fn expected_length(&self) -> usize {
-        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.addresses.as_slice().len()
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.profile.as_slice().len()
+            + self.addresses.as_slice().len()
+            + self.custom.as_slice().len()
+            + self.dweb.as_slice().len()
     }
     fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
         let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
         let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
         offsets.push(total_size);
+        total_size += self.profile.as_slice().len();
+        offsets.push(total_size);
         total_size += self.addresses.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.custom.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.dweb.as_slice().len();
         writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
         for offset in offsets.into_iter() {
             writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
         }
+        writer.write_all(self.profile.as_slice())?;
         writer.write_all(self.addresses.as_slice())?;
+        writer.write_all(self.custom.as_slice())?;
+        writer.write_all(self.dweb.as_slice())?;
         Ok(())
     }
     fn build(&self) -> Self::Entity {
@@ -0,0 +1,140 @@
+//! BLAKE2b with 32-byte digests, see RFC 7693. CKB hashes with the personalization
+//! `ckb-default-hash`.
+
+use core::cmp;
+
+const BLOCK_LEN: usize = 128;
+const HASH_LEN: usize = 32;
+
+/// Personalization of the hashes of CKB
+pub const CKB_PERSONALIZATION: &[u8; 16] = b"ckb-default-hash";
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+fn mix(v: &mut [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+fn compress(state: &mut [u64; 8], block: &[u8; BLOCK_LEN], counter: u128, last: bool) {
+    let mut m = [0u64; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks_exact(8)) {
+        let mut le = [0u8; 8];
+        le.copy_from_slice(bytes);
+        *word = u64::from_le_bytes(le);
+    }
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(state);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= counter as u64;
+    v[13] ^= (counter >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for s in SIGMA.iter() {
+        mix(&mut v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        mix(&mut v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        mix(&mut v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        mix(&mut v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        mix(&mut v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        mix(&mut v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        mix(&mut v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        mix(&mut v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+    }
+
+    for (i, value) in state.iter_mut().enumerate() {
+        *value ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// Hash written in parts, the last block is only compressed by `finalize`
+pub struct Blake2b {
+    state: [u64; 8],
+    buffer: [u8; BLOCK_LEN],
+    buffer_len: usize,
+    counter: u128,
+}
+
+impl Blake2b {
+    pub fn new(personalization: &[u8; 16]) -> Self {
+        let mut state = IV;
+        state[0] ^= 0x0101_0000 ^ HASH_LEN as u64;
+        for (value, bytes) in state[6..].iter_mut().zip(personalization.chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(bytes);
+            *value ^= u64::from_le_bytes(le);
+        }
+
+        Blake2b {
+            state,
+            buffer: [0u8; BLOCK_LEN],
+            buffer_len: 0,
+            counter: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.buffer_len == BLOCK_LEN {
+                self.counter += BLOCK_LEN as u128;
+                compress(&mut self.state, &self.buffer, self.counter, false);
+                self.buffer_len = 0;
+            }
+            let len = cmp::min(BLOCK_LEN - self.buffer_len, data.len());
+            self.buffer[self.buffer_len..self.buffer_len + len].copy_from_slice(&data[..len]);
+            self.buffer_len += len;
+            data = &data[len..];
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; HASH_LEN] {
+        self.counter += self.buffer_len as u128;
+        self.buffer[self.buffer_len..].fill(0);
+        compress(&mut self.state, &self.buffer, self.counter, true);
+
+        let mut hash = [0u8; HASH_LEN];
+        for (bytes, value) in hash.chunks_exact_mut(8).zip(self.state.iter()) {
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// `ckbhash`, the BLAKE2b-256 of CKB
+pub fn ckb_hash(data: &[u8]) -> [u8; HASH_LEN] {
+    let mut hasher = Blake2b::new(CKB_PERSONALIZATION);
+    hasher.update(data);
+    hasher.finalize()
+}
@@ -0,0 +1,219 @@
+//! Validation of JSON records in place, without allocation.
+//!
+//! The record is read as `StoreType['data']`: an object of sections, each an array of entries
+//! with a string `key`, a string `value` and an optional string `label`. Like the serde path,
+//! unknown members are skipped after they are checked to be valid JSON. The grammar is the one
+//! of `json_stream::Validator`, run here over a whole record.
+
+use crate::json_stream::{Span, Validator};
+use core::str;
+use serde::{Deserialize, Serialize};
+
+/// Same nesting limit as `serde_json`
+pub(crate) const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonRecordError {
+    InvalidUtf8,
+    UnexpectedEnd,
+    UnexpectedByte,
+    /// A control character, an unknown escape or a lone surrogate in a string
+    InvalidString,
+    /// A section which is not an array of objects, or an entry field which is not a string
+    InvalidType,
+    DuplicateSection,
+    DuplicateField,
+    MissingField,
+    TooDeep,
+    TrailingData,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    Profile,
+    Addresses,
+    Custom,
+    Dweb,
+}
+
+impl Section {
+    /// In the order of `KuaiMvpView::sections`
+    pub const ALL: [Section; 4] = [
+        Section::Profile,
+        Section::Addresses,
+        Section::Custom,
+        Section::Dweb,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Section> {
+        Section::ALL.into_iter().find(|section| section.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Section::Profile => "profile",
+            Section::Addresses => "addresses",
+            Section::Custom => "custom",
+            Section::Dweb => "dweb",
+        }
+    }
+
+    pub(crate) fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+/// Content of a JSON string between its quotes, escapes are kept as they are
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsonStr<'a> {
+    raw: &'a str,
+    escaped: bool,
+}
+
+impl<'a> JsonStr<'a> {
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// The string itself, when it has no escapes to decode
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.escaped {
+            None
+        } else {
+            Some(self.raw)
+        }
+    }
+
+    /// Bytes of the string with its escapes decoded
+    pub fn bytes(&self) -> Unescape<str::Bytes<'a>> {
+        Unescape::new(self.raw.bytes(), self.escaped)
+    }
+
+    pub fn eq_str(&self, s: &str) -> bool {
+        match self.as_str() {
+            Some(raw) => raw == s,
+            None => self.bytes().eq(s.bytes()),
+        }
+    }
+
+    /// Length in bytes of the decoded string
+    pub fn len(&self) -> usize {
+        match self.as_str() {
+            Some(raw) => raw.len(),
+            None => self.bytes().count(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+}
+
+/// Bytes of a validated JSON string with its escapes decoded, from wherever the string is read
+pub struct Unescape<I> {
+    bytes: I,
+    escaped: bool,
+    /// UTF-8 of a `\u` escape not returned yet
+    pending: [u8; 4],
+    pending_start: usize,
+    pending_end: usize,
+}
+
+impl<I: Iterator<Item = u8>> Unescape<I> {
+    /// Without `escaped`, the bytes are the string itself
+    pub fn new(bytes: I, escaped: bool) -> Self {
+        Unescape {
+            bytes,
+            escaped,
+            pending: [0; 4],
+            pending_start: 0,
+            pending_end: 0,
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            value = (value << 4) | (self.bytes.next()? as char).to_digit(16)?;
+        }
+        Some(value)
+    }
+
+    fn unicode(&mut self) -> Option<u8> {
+        let unit = self.hex4()?;
+        let c = if (0xd800..0xdc00).contains(&unit) {
+            // the low surrogate follows, checked by the validator
+            self.bytes.nth(1)?;
+            let low = self.hex4()?;
+            char::from_u32(0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00))?
+        } else {
+            char::from_u32(unit)?
+        };
+        self.pending_end = c.encode_utf8(&mut self.pending).len();
+        self.pending_start = 1;
+        Some(self.pending[0])
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Unescape<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pending_start < self.pending_end {
+            self.pending_start += 1;
+            return Some(self.pending[self.pending_start - 1]);
+        }
+
+        let b = self.bytes.next()?;
+        if !self.escaped || b != b'\\' {
+            return Some(b);
+        }
+
+        match self.bytes.next()? {
+            b'b' => Some(0x08),
+            b'f' => Some(0x0c),
+            b'n' => Some(b'\n'),
+            b'r' => Some(b'\r'),
+            b't' => Some(b'\t'),
+            b'u' => self.unicode(),
+            // '"', '\\' and '/'
+            escaped => Some(escaped),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawEntry<'a> {
+    pub key: JsonStr<'a>,
+    pub value: JsonStr<'a>,
+    pub label: Option<JsonStr<'a>>,
+}
+
+/// Validate a JSON record and call `visit` on each of its entries in order
+pub fn visit<'a, F>(data: &'a [u8], mut visit: F) -> Result<(), JsonRecordError>
+where
+    F: FnMut(Section, RawEntry<'a>),
+{
+    let data = str::from_utf8(data).map_err(|_| JsonRecordError::InvalidUtf8)?;
+    // quotes are ASCII, so fields start and end on char boundaries
+    let field = |span: Span| JsonStr {
+        raw: &data[span.start..span.end],
+        escaped: span.escaped,
+    };
+
+    let mut validator = Validator::new();
+    validator.feed(data.as_bytes(), |section, entry| {
+        let entry = RawEntry {
+            key: field(entry.key),
+            value: field(entry.value),
+            label: entry.label.map(field),
+        };
+        visit(section, entry);
+    });
+    validator.finish()
+}
+
+pub fn validate(data: &[u8]) -> Result<(), JsonRecordError> {
+    visit(data, |_, _| {})
+}
@@ -0,0 +1,715 @@
+//! Validation of JSON records fed in chunks, for records too large to be loaded at once.
+//!
+//! `Validator` reads the record one byte at a time with a fixed amount of state, so the cell
+//! data can be read in windows of any size, and `json_record::visit` runs it over a whole
+//! slice. Entries are reported with the positions of their fields in the record, nothing is
+//! copied or allocated.
+//!
+//! The validator also follows whether the record is in the canonical form of `canonical_json`.
+
+use crate::json_record::{JsonRecordError, Section, MAX_DEPTH};
+
+/// Long enough for the names of sections and entry fields, longer names match none of them
+const NAME_CAPACITY: usize = 16;
+
+/// Fields of an entry in canonical form, ordered by name
+const CANONICAL_FIELDS: [Field; 3] = [Field::Key, Field::Label, Field::Value];
+
+/// Control characters with an escape of their own, such as `\n`
+const SHORT_ESCAPES: [u32; 5] = [0x08, 0x09, 0x0a, 0x0c, 0x0d];
+
+/// A string field between its quotes, as offsets from the first byte fed to the validator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    /// Length in bytes of the decoded string
+    pub len: usize,
+    pub escaped: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntrySpans {
+    pub key: Span,
+    pub value: Span,
+    pub label: Option<Span>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Before the record object
+    Start,
+    /// After `{` of the record
+    TopMemberOrEnd,
+    /// After `,` between sections
+    TopMember,
+    TopColon,
+    TopValue,
+    TopNext,
+    /// After `[` of a section
+    EntryOrEnd,
+    /// After `,` between entries
+    Entry,
+    EntryNext,
+    /// After `{` of an entry
+    FieldOrEnd,
+    /// After `,` between fields
+    Field,
+    FieldColon,
+    FieldValue,
+    FieldNext,
+    /// Inside a skipped value, the containers are on the skip stack
+    SkipValue,
+    /// After `[` of a skipped array
+    SkipValueOrEnd,
+    /// After `{` of a skipped object
+    SkipMemberOrEnd,
+    SkipMember,
+    SkipColon,
+    SkipNext,
+    Literal(&'static [u8], usize),
+    Number(Number),
+    /// After the record, only whitespace may follow
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Number {
+    Minus,
+    Zero,
+    Integer,
+    Dot,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigits,
+}
+
+impl Number {
+    /// Whether the number may end here
+    fn is_complete(&self) -> bool {
+        matches!(
+            self,
+            Number::Zero | Number::Integer | Number::Fraction | Number::ExponentDigits
+        )
+    }
+
+    fn next(&self, b: u8) -> Option<Number> {
+        match (self, b) {
+            (Number::Minus, b'0') => Some(Number::Zero),
+            (Number::Minus, b'1'..=b'9') => Some(Number::Integer),
+            (Number::Integer, b'0'..=b'9') => Some(Number::Integer),
+            (Number::Zero | Number::Integer, b'.') => Some(Number::Dot),
+            (Number::Dot | Number::Fraction, b'0'..=b'9') => Some(Number::Fraction),
+            (Number::Zero | Number::Integer | Number::Fraction, b'e' | b'E') => {
+                Some(Number::Exponent)
+            }
+            (Number::Exponent, b'+' | b'-') => Some(Number::ExponentSign),
+            (Number::Exponent | Number::ExponentSign | Number::ExponentDigits, b'0'..=b'9') => {
+                Some(Number::ExponentDigits)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What the string being read is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    SectionName,
+    FieldName,
+    FieldValue,
+    Skipped,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Escape {
+    None,
+    Backslash,
+    Hex { count: u8, unit: u32 },
+    /// A high surrogate waits for `\` and `u` of its low surrogate
+    LowBackslash { high: u32 },
+    LowU { high: u32 },
+    LowHex { high: u32, count: u8, unit: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Key,
+    Value,
+    Label,
+    Other,
+}
+
+/// Incremental UTF-8 check, the bounds of the next continuation byte exclude overlong
+/// encodings and surrogates the way `core::str::from_utf8` does
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Utf8 {
+    pending: u8,
+    low: u8,
+    high: u8,
+    valid: bool,
+}
+
+impl Utf8 {
+    pub(crate) fn new() -> Self {
+        Utf8 {
+            pending: 0,
+            low: 0x80,
+            high: 0xbf,
+            valid: true,
+        }
+    }
+
+    pub(crate) fn push(&mut self, b: u8) {
+        if !self.valid {
+            return;
+        }
+
+        if self.pending > 0 {
+            if b < self.low || b > self.high {
+                self.valid = false;
+            }
+            self.pending -= 1;
+            self.low = 0x80;
+            self.high = 0xbf;
+            return;
+        }
+
+        let (pending, low, high) = match b {
+            0x00..=0x7f => (0, 0x80, 0xbf),
+            0xc2..=0xdf => (1, 0x80, 0xbf),
+            0xe0 => (2, 0xa0, 0xbf),
+            0xed => (2, 0x80, 0x9f),
+            0xe1..=0xef => (2, 0x80, 0xbf),
+            0xf0 => (3, 0x90, 0xbf),
+            0xf1..=0xf3 => (3, 0x80, 0xbf),
+            0xf4 => (3, 0x80, 0x8f),
+            _ => {
+                self.valid = false;
+                return;
+            }
+        };
+        self.pending = pending;
+        self.low = low;
+        self.high = high;
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.valid && self.pending == 0
+    }
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+pub struct Validator {
+    state: State,
+    /// The target of the string being read, if any
+    string: Option<Target>,
+    escape: Escape,
+    /// Decoded name of the member being read
+    name: [u8; NAME_CAPACITY],
+    name_len: usize,
+    /// Decoded length of the field being read
+    field_len: usize,
+    /// Position of the byte being read and of the string being read
+    offset: usize,
+    string_start: usize,
+    escaped: bool,
+    /// Containers of the skipped value, a set bit for an object
+    skip_stack: u128,
+    skip_depth: usize,
+    /// Depth of the skipped value in the record, with the state to return to after it
+    skip_base: usize,
+    skip_return: State,
+    section: Option<Section>,
+    seen: u8,
+    field: Field,
+    /// Whether the bytes read so far are in canonical form, with the last section read and the
+    /// number of fields read in the entry being read
+    canonical: bool,
+    last_section: Option<Section>,
+    field_count: usize,
+    key: Option<Span>,
+    value: Option<Span>,
+    label: Option<Span>,
+    /// An entry completed by the last byte, for the caller of `step`
+    entry: Option<(Section, EntrySpans)>,
+    utf8: Utf8,
+    error: Option<JsonRecordError>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator::new()
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator {
+            state: State::Start,
+            string: None,
+            escape: Escape::None,
+            name: [0; NAME_CAPACITY],
+            name_len: 0,
+            field_len: 0,
+            offset: 0,
+            string_start: 0,
+            escaped: false,
+            skip_stack: 0,
+            skip_depth: 0,
+            skip_base: 0,
+            skip_return: State::Done,
+            section: None,
+            seen: 0,
+            field: Field::Other,
+            canonical: true,
+            last_section: None,
+            field_count: 0,
+            key: None,
+            value: None,
+            label: None,
+            entry: None,
+            utf8: Utf8::new(),
+            error: None,
+        }
+    }
+
+    /// Read the next chunk of the record and call `visit` on each entry it completes.
+    ///
+    /// Errors are reported by `finish`, once the whole record is read, so invalid UTF-8 is
+    /// reported the same way as `json_record::visit` wherever it is
+    pub fn feed<F>(&mut self, chunk: &[u8], mut visit: F)
+    where
+        F: FnMut(Section, EntrySpans),
+    {
+        for &b in chunk {
+            self.push(b);
+            if let Some((section, entry)) = self.entry.take() {
+                visit(section, entry);
+            }
+        }
+    }
+
+    /// Read `chunk` up to the end of the next entry, returns the number of bytes read with the
+    /// entry if one was completed. Errors are reported by `finish`, as with `feed`
+    pub fn feed_entry(&mut self, chunk: &[u8]) -> (usize, Option<(Section, EntrySpans)>) {
+        for (index, &b) in chunk.iter().enumerate() {
+            self.push(b);
+            if let Some(entry) = self.entry.take() {
+                return (index + 1, Some(entry));
+            }
+        }
+        (chunk.len(), None)
+    }
+
+    fn push(&mut self, b: u8) {
+        self.utf8.push(b);
+        if self.error.is_none() {
+            if let Err(err) = self.step(b) {
+                self.error = Some(err);
+            }
+        }
+        self.offset += 1;
+    }
+
+    /// Whether the record read so far is in the canonical form of `canonical_json`, a record
+    /// failing `finish` is not canonical either
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    pub fn finish(self) -> Result<(), JsonRecordError> {
+        if !self.utf8.is_complete() {
+            return Err(JsonRecordError::InvalidUtf8);
+        }
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        if self.state != State::Done {
+            return Err(JsonRecordError::UnexpectedEnd);
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, b: u8) -> Result<(), JsonRecordError> {
+        if let Some(target) = self.string {
+            return self.string_step(target, b);
+        }
+
+        match self.state {
+            State::Literal(literal, index) => {
+                if b != literal[index] {
+                    return Err(JsonRecordError::UnexpectedByte);
+                }
+                if index + 1 == literal.len() {
+                    self.value_done();
+                } else {
+                    self.state = State::Literal(literal, index + 1);
+                }
+                return Ok(());
+            }
+            State::Number(number) => {
+                if let Some(next) = number.next(b) {
+                    self.state = State::Number(next);
+                    return Ok(());
+                }
+                if !number.is_complete() {
+                    return Err(JsonRecordError::UnexpectedByte);
+                }
+                // the byte ending the number belongs to the container
+                self.value_done();
+                return self.step(b);
+            }
+            _ => {}
+        }
+
+        if is_whitespace(b) {
+            self.canonical = false;
+            return Ok(());
+        }
+
+        match (self.state, b) {
+            (State::Start, b'{') => self.state = State::TopMemberOrEnd,
+            (State::TopMemberOrEnd, b'}') => self.state = State::Done,
+            (State::TopMemberOrEnd | State::TopMember, b'"') => {
+                self.begin_string(Target::SectionName)
+            }
+            (State::TopColon, b':') => self.state = State::TopValue,
+            (State::TopValue, _) => match self.section {
+                Some(section) => {
+                    if self.seen & section.bit() != 0 {
+                        return Err(JsonRecordError::DuplicateSection);
+                    }
+                    self.seen |= section.bit();
+                    if matches!(self.last_section, Some(last) if last.name() > section.name()) {
+                        self.canonical = false;
+                    }
+                    self.last_section = Some(section);
+                    if b != b'[' {
+                        return Err(JsonRecordError::InvalidType);
+                    }
+                    self.state = State::EntryOrEnd;
+                }
+                None => {
+                    self.canonical = false;
+                    return self.begin_skip(1, State::TopNext, b);
+                }
+            },
+            (State::TopNext, b',') => self.state = State::TopMember,
+            (State::TopNext, b'}') => self.state = State::Done,
+
+            (State::EntryOrEnd, b']') => {
+                // empty sections are left out
+                self.canonical = false;
+                self.state = State::TopNext;
+            }
+            (State::EntryNext, b']') => self.state = State::TopNext,
+            (State::EntryOrEnd | State::Entry, b'{') => {
+                self.key = None;
+                self.value = None;
+                self.label = None;
+                self.field_count = 0;
+                self.state = State::FieldOrEnd;
+            }
+            (State::EntryOrEnd | State::Entry, _) => return Err(JsonRecordError::InvalidType),
+            (State::EntryNext, b',') => self.state = State::Entry,
+
+            (State::FieldOrEnd, b'}') | (State::FieldNext, b'}') => {
+                let entry = EntrySpans {
+                    key: self.key.ok_or(JsonRecordError::MissingField)?,
+                    value: self.value.ok_or(JsonRecordError::MissingField)?,
+                    label: self.label,
+                };
+                if self.field_count != CANONICAL_FIELDS.len() {
+                    self.canonical = false;
+                }
+                self.entry = self.section.map(|section| (section, entry));
+                self.state = State::EntryNext;
+            }
+            (State::FieldOrEnd | State::Field, b'"') => self.begin_string(Target::FieldName),
+            (State::FieldColon, b':') => self.state = State::FieldValue,
+            (State::FieldValue, _) => {
+                let slot = match self.field {
+                    Field::Key => self.key,
+                    Field::Value => self.value,
+                    Field::Label => self.label,
+                    Field::Other => return self.begin_skip(3, State::FieldNext, b),
+                };
+                if slot.is_some() {
+                    return Err(JsonRecordError::DuplicateField);
+                }
+                if b != b'"' {
+                    return Err(JsonRecordError::InvalidType);
+                }
+                self.begin_string(Target::FieldValue);
+            }
+            (State::FieldNext, b',') => self.state = State::Field,
+
+            (State::SkipValueOrEnd, b']') => self.end_container(false)?,
+            (State::SkipValue | State::SkipValueOrEnd, _) => self.skip_value(b)?,
+            (State::SkipMemberOrEnd, b'}') => self.end_container(true)?,
+            (State::SkipMemberOrEnd | State::SkipMember, b'"') => {
+                self.begin_string(Target::Skipped)
+            }
+            (State::SkipColon, b':') => self.state = State::SkipValue,
+            (State::SkipNext, b',') => {
+                self.state = if self.in_object() {
+                    State::SkipMember
+                } else {
+                    State::SkipValue
+                };
+            }
+            (State::SkipNext, b']') => self.end_container(false)?,
+            (State::SkipNext, b'}') => self.end_container(true)?,
+
+            (State::Done, _) => return Err(JsonRecordError::TrailingData),
+            _ => return Err(JsonRecordError::UnexpectedByte),
+        }
+        Ok(())
+    }
+
+    fn begin_string(&mut self, target: Target) {
+        self.string = Some(target);
+        self.escape = Escape::None;
+        self.name_len = 0;
+        self.field_len = 0;
+        self.string_start = self.offset + 1;
+        self.escaped = false;
+    }
+
+    fn push_byte(&mut self, b: u8) {
+        if self.name_len < NAME_CAPACITY {
+            self.name[self.name_len] = b;
+        }
+        self.name_len = self.name_len.saturating_add(1);
+        self.field_len += 1;
+    }
+
+    fn push_char(&mut self, unit: u32) -> Result<(), JsonRecordError> {
+        let c = char::from_u32(unit).ok_or(JsonRecordError::InvalidString)?;
+        let mut bytes = [0u8; 4];
+        for b in c.encode_utf8(&mut bytes).bytes() {
+            self.push_byte(b);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &[u8] {
+        if self.name_len > NAME_CAPACITY {
+            return &[];
+        }
+        &self.name[..self.name_len]
+    }
+
+    fn string_step(&mut self, target: Target, b: u8) -> Result<(), JsonRecordError> {
+        let hex = || (b as char).to_digit(16).ok_or(JsonRecordError::InvalidString);
+
+        match self.escape {
+            Escape::None => match b {
+                b'"' => {
+                    self.string = None;
+                    self.string_done(target);
+                }
+                b'\\' => {
+                    self.escape = Escape::Backslash;
+                    self.escaped = true;
+                }
+                b if b < 0x20 => return Err(JsonRecordError::InvalidString),
+                b => self.push_byte(b),
+            },
+            Escape::Backslash => {
+                self.escape = Escape::None;
+                match b {
+                    b'"' | b'\\' => self.push_byte(b),
+                    b'/' => {
+                        self.canonical = false;
+                        self.push_byte(b);
+                    }
+                    b'b' => self.push_byte(0x08),
+                    b'f' => self.push_byte(0x0c),
+                    b'n' => self.push_byte(b'\n'),
+                    b'r' => self.push_byte(b'\r'),
+                    b't' => self.push_byte(b'\t'),
+                    b'u' => self.escape = Escape::Hex { count: 0, unit: 0 },
+                    _ => return Err(JsonRecordError::InvalidString),
+                }
+            }
+            Escape::Hex { count, unit } => {
+                let unit = (unit << 4) | hex()?;
+                if b.is_ascii_uppercase() {
+                    self.canonical = false;
+                }
+                if count < 3 {
+                    self.escape = Escape::Hex {
+                        count: count + 1,
+                        unit,
+                    };
+                    return Ok(());
+                }
+
+                // only control characters without a short escape are written as `\u`
+                if unit >= 0x20 || SHORT_ESCAPES.contains(&unit) {
+                    self.canonical = false;
+                }
+                if (0xdc00..0xe000).contains(&unit) {
+                    return Err(JsonRecordError::InvalidString);
+                } else if (0xd800..0xdc00).contains(&unit) {
+                    self.escape = Escape::LowBackslash { high: unit };
+                } else {
+                    self.escape = Escape::None;
+                    self.push_char(unit)?;
+                }
+            }
+            Escape::LowBackslash { high } => {
+                if b != b'\\' {
+                    return Err(JsonRecordError::InvalidString);
+                }
+                self.escape = Escape::LowU { high };
+            }
+            Escape::LowU { high } => {
+                if b != b'u' {
+                    return Err(JsonRecordError::InvalidString);
+                }
+                self.escape = Escape::LowHex {
+                    high,
+                    count: 0,
+                    unit: 0,
+                };
+            }
+            Escape::LowHex { high, count, unit } => {
+                let unit = (unit << 4) | hex()?;
+                if count < 3 {
+                    self.escape = Escape::LowHex {
+                        high,
+                        count: count + 1,
+                        unit,
+                    };
+                } else if (0xdc00..0xe000).contains(&unit) {
+                    self.escape = Escape::None;
+                    self.push_char(0x10000 + ((high - 0xd800) << 10) + (unit - 0xdc00))?;
+                } else {
+                    return Err(JsonRecordError::InvalidString);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn string_done(&mut self, target: Target) {
+        match target {
+            Target::SectionName => {
+                let name = self.name();
+                self.section = Section::ALL
+                    .iter()
+                    .find(|section| section.name().as_bytes() == name)
+                    .copied();
+                self.state = State::TopColon;
+            }
+            Target::FieldName => {
+                self.field = match self.name() {
+                    b"key" => Field::Key,
+                    b"value" => Field::Value,
+                    b"label" => Field::Label,
+                    _ => Field::Other,
+                };
+                if CANONICAL_FIELDS.get(self.field_count) != Some(&self.field) {
+                    self.canonical = false;
+                }
+                self.field_count += 1;
+                self.state = State::FieldColon;
+            }
+            Target::FieldValue => {
+                let span = Some(Span {
+                    start: self.string_start,
+                    end: self.offset,
+                    len: self.field_len,
+                    escaped: self.escaped,
+                });
+                match self.field {
+                    Field::Key => self.key = span,
+                    Field::Value => self.value = span,
+                    Field::Label => self.label = span,
+                    Field::Other => {}
+                }
+                self.state = State::FieldNext;
+            }
+            Target::Skipped => {
+                // a member name of a skipped object is followed by its value
+                self.state = if self.state == State::SkipMemberOrEnd
+                    || self.state == State::SkipMember
+                {
+                    State::SkipColon
+                } else {
+                    self.value_done();
+                    return;
+                };
+            }
+        }
+    }
+
+    /// Skip the value starting with `b`, at `base` in the record
+    fn begin_skip(&mut self, base: usize, next: State, b: u8) -> Result<(), JsonRecordError> {
+        self.skip_base = base;
+        self.skip_return = next;
+        self.skip_stack = 0;
+        self.skip_depth = 0;
+        self.skip_value(b)
+    }
+
+    fn skip_value(&mut self, b: u8) -> Result<(), JsonRecordError> {
+        if self.skip_base + self.skip_depth > MAX_DEPTH {
+            return Err(JsonRecordError::TooDeep);
+        }
+
+        match b {
+            b'"' => {
+                self.state = State::SkipValue;
+                self.begin_string(Target::Skipped);
+            }
+            b'{' | b'[' => {
+                if b == b'{' {
+                    self.skip_stack |= 1u128 << self.skip_depth;
+                    self.state = State::SkipMemberOrEnd;
+                } else {
+                    self.skip_stack &= !(1u128 << self.skip_depth);
+                    self.state = State::SkipValueOrEnd;
+                }
+                self.skip_depth += 1;
+            }
+            b't' => self.state = State::Literal(b"true", 1),
+            b'f' => self.state = State::Literal(b"false", 1),
+            b'n' => self.state = State::Literal(b"null", 1),
+            b'-' => self.state = State::Number(Number::Minus),
+            b'0' => self.state = State::Number(Number::Zero),
+            b'1'..=b'9' => self.state = State::Number(Number::Integer),
+            _ => return Err(JsonRecordError::UnexpectedByte),
+        }
+        Ok(())
+    }
+
+    fn in_object(&self) -> bool {
+        self.skip_stack & (1u128 << (self.skip_depth - 1)) != 0
+    }
+
+    fn end_container(&mut self, object: bool) -> Result<(), JsonRecordError> {
+        if self.skip_depth == 0 || self.in_object() != object {
+            return Err(JsonRecordError::UnexpectedByte);
+        }
+        self.skip_depth -= 1;
+        self.value_done();
+        Ok(())
+    }
+
+    /// A skipped value is complete
+    fn value_done(&mut self) {
+        self.state = if self.skip_depth > 0 {
+            State::SkipNext
+        } else {
+            self.skip_return
+        };
+    }
+}
@@ -1,88 +1,276 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-#[cfg(not(feature = "std"))]
-
-extern crate no_std_compat as std;
-
+use crate::address::{self, ChainAddressError};
+use crate::canonical_json::{self, CanonicalJsonError};
+use crate::diff::{self, Change};
+use crate::dweb::{self, DwebError};
 use crate::generated::basic::{MString};
-use crate::generated::mvp_data::{KuaiMvpData, KuaiMvpDataReader, Obj, Objs};
+use crate::json_record::{self, JsonRecordError, JsonStr, Section};
+use crate::label::{self, LabelError};
+use crate::patch::{self, PatchError, PatchOperation};
+use crate::path::{self, PathError, PathSegment, PathValue};
+use crate::generated::mvp_data::{KuaiMvpData, Obj, Objs};
+use crate::record_reader::{MoleculeError, RecordReader};
 use molecule::{
     bytes::Bytes,
     error::VerificationError,
-    prelude::{Builder, Entity},
+    prelude::{Builder, Byte, Entity},
 };
-use molecule::prelude::Reader;
 
 use serde_json;
 use serde::{Deserialize, Serialize};
 
-extern crate alloc;
 use alloc::string::String;
 use alloc::vec::Vec;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewError {
+    InvalidJson,
+    InvalidUtf8,
+    InvalidMolecule,
+}
+
+impl From<JsonRecordError> for ViewError {
+    fn from(err: JsonRecordError) -> Self {
+        match err {
+            JsonRecordError::InvalidUtf8 => ViewError::InvalidUtf8,
+            _ => ViewError::InvalidJson,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Data {
     pub key: String,
     pub value: String,
+    #[serde(default)]
+    pub label: String,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Mirror of `StoreType['data']` in `src/actors/record.model.ts`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct KuaiMvpView {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profile: Vec<Data>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub addresses: Vec<Data>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<Data>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dweb: Vec<Data>,
+}
+
+fn as_mstring(s: &str) -> MString {
+    MString::new_builder()
+        .set(s.as_bytes().iter().map(|b| Byte::new(*b)).collect())
+        .build()
+}
+
+fn as_objs(items: &[Data]) -> Objs {
+    Objs::new_builder()
+        .extend(items.iter().map(|data| data.as_molecule_data()))
+        .build()
+}
+
+fn from_json_str(s: JsonStr) -> String {
+    match s.as_str() {
+        Some(s) => String::from(s),
+        // decoded escapes of a validated string are UTF-8, nothing is replaced
+        None => String::from_utf8_lossy(&s.bytes().collect::<Vec<u8>>()).into_owned(),
+    }
 }
 
 impl Data {
     pub fn as_molecule_data(&self) -> Obj {
-        let key = MString::new_unchecked(Bytes::from(self.key.as_bytes().to_vec()));
-        let value = MString::new_unchecked(Bytes::from(self.value.as_bytes().to_vec()));
-
-        Obj::new_builder().key(key).value(value).build()
+        Obj::new_builder()
+            .key(as_mstring(&self.key))
+            .value(as_mstring(&self.value))
+            .label(as_mstring(&self.label))
+            .build()
     }
 }
 
 impl KuaiMvpView {
-    pub fn new(data: &[u8]) -> Result<KuaiMvpView, VerificationError> {
-        let data_reader = KuaiMvpDataReader::new_unchecked(data);
-        let objs = data_reader.addresses().to_entity();
-        let mut addresses = Vec::new();
-        for i in 0..objs.item_count() {
-            let key = objs.get(i).unwrap().key();
-            let value = objs.get(i).unwrap().value();
-            addresses.push(Data{
-                key: String::from_utf8(key.as_slice().to_vec()).unwrap(),
-                value: String::from_utf8(value.as_slice().to_vec()).unwrap(),
-            })
-        }
+    pub fn new(data: &[u8]) -> Result<KuaiMvpView, ViewError> {
+        Self::from_molecule(data, true)
+    }
 
-        return Ok(KuaiMvpView{ addresses })
+    /// Records with fields unknown to this crate are only accepted when `compatible` is set
+    pub fn from_molecule(data: &[u8], compatible: bool) -> Result<KuaiMvpView, ViewError> {
+        Ok(Self::from_molecule_verified(data, compatible)?)
+    }
 
+    /// Same as `from_molecule` with the entry and the field that failed, for indexers reading
+    /// records from any cell
+    pub fn from_molecule_verified(
+        data: &[u8],
+        compatible: bool,
+    ) -> Result<KuaiMvpView, MoleculeError> {
+        Ok(RecordReader::verified(data, compatible)?.to_view())
     }
 
     pub fn as_molecule_data(&self) -> Result<Bytes, VerificationError> {
-        let mut objs_build = Objs::new_builder();
+        Ok(KuaiMvpData::new_builder()
+            .profile(as_objs(&self.profile))
+            .addresses(as_objs(&self.addresses))
+            .custom(as_objs(&self.custom))
+            .dweb(as_objs(&self.dweb))
+            .build()
+            .as_bytes())
+    }
 
-        for data in self.addresses.iter() {
-            objs_build = objs_build.push(data.as_molecule_data());
-        }
+    pub fn as_json_str(data: &str) -> Result<KuaiMvpView, ViewError> {
+        serde_json::from_str(data).map_err(|_| ViewError::InvalidJson)
+    }
 
-        let objs = objs_build.build();
+    /// Same records as `as_json_str`, checked by `json_record::visit` in place so only the
+    /// entries themselves are allocated
+    pub fn from_json_slice(data: &[u8]) -> Result<KuaiMvpView, ViewError> {
+        let mut view = KuaiMvpView::default();
+        json_record::visit(data, |section, entry| {
+            view.push(
+                section,
+                Data {
+                    key: from_json_str(entry.key),
+                    value: from_json_str(entry.value),
+                    label: entry.label.map(from_json_str).unwrap_or_default(),
+                },
+            );
+        })
+        .map_err(ViewError::from)?;
 
-        Ok(KuaiMvpData::new_builder().addresses(objs).build().as_bytes())
+        Ok(view)
     }
 
-    pub fn as_json_str(data: &str) -> KuaiMvpView {
-        let view: KuaiMvpView = serde_json::from_str(data).unwrap();
-        view
+    /// Append an entry to `section`, in the order of the record
+    pub fn push(&mut self, section: Section, data: Data) {
+        let items = match section {
+            Section::Profile => &mut self.profile,
+            Section::Addresses => &mut self.addresses,
+            Section::Custom => &mut self.custom,
+            Section::Dweb => &mut self.dweb,
+        };
+        items.push(data);
     }
 
-    pub fn verify(&self) -> bool {
-        if self.addresses.is_empty() {
-            return false
+    /// Same record as `serde_json::to_string` with the sorted keys and minimal escapes of
+    /// `canonical_json`, so equal records always get equal data
+    pub fn as_canonical_json(&self) -> Result<String, CanonicalJsonError> {
+        canonical_json::to_string(self)
+    }
+
+    /// Entries added, removed or changed from `old` to `new`, see `diff::diff`
+    pub fn diff(old: &KuaiMvpView, new: &KuaiMvpView) -> Vec<Change> {
+        diff::diff(old, new)
+    }
+
+    /// Apply a RFC 7396 merge patch, see `patch::merge_patch`
+    pub fn merge_patch(&mut self, patch: &serde_json::Value) -> Result<(), PatchError> {
+        patch::merge_patch(self, patch)
+    }
+
+    /// Apply the `add`, `remove` and `replace` operations of a RFC 6902 patch, see
+    /// `patch::apply_patch`
+    pub fn apply_patch(&mut self, patch: &[PatchOperation]) -> Result<(), PatchError> {
+        patch::apply_patch(self, patch)
+    }
+
+    /// Value at a `StorePath` such as `['data', 'addresses', 0, 'value']`, see `path`
+    pub fn get(&self, path: &[PathSegment]) -> Result<PathValue<'_>, PathError> {
+        path::get(self, path)
+    }
+
+    pub fn set(
+        &mut self,
+        path: &[PathSegment],
+        value: &serde_json::Value,
+    ) -> Result<(), PathError> {
+        path::set(self, path, value)
+    }
+
+    pub fn remove(&mut self, path: &[PathSegment]) -> Result<(), PathError> {
+        path::remove(self, path)
+    }
+
+    /// Sections in the order of `StoreType['data']`
+    pub fn sections(&self) -> [(&'static str, &[Data]); 4] {
+        [
+            ("profile", self.profile.as_slice()),
+            ("addresses", self.addresses.as_slice()),
+            ("custom", self.custom.as_slice()),
+            ("dweb", self.dweb.as_slice()),
+        ]
+    }
+
+    /// Value of the leading "ckb" entry of `addresses`
+    pub fn ckb_address(&self) -> Option<&str> {
+        self.addresses
+            .first()
+            .filter(|data| data.key == "ckb")
+            .map(|data| data.value.as_str())
+    }
+
+    /// Section and key of an entry sharing its key with another entry of the same section
+    pub fn duplicate_key(&self) -> Option<(&'static str, &str)> {
+        for (name, items) in self.sections() {
+            let mut keys: Vec<&str> = items.iter().map(|data| data.key.as_str()).collect();
+            keys.sort_unstable();
+            if let Some(pair) = keys.windows(2).find(|pair| pair[0] == pair[1]) {
+                return Some((name, pair[0]));
+            }
         }
+        None
+    }
 
-        if self.addresses.get(0).unwrap().key != "ckb" {
-            return false
+    /// Whether the keys of every section are strictly ascending, which also makes them unique.
+    /// The leading "ckb" entry of `addresses` stays first and must not appear again.
+    pub fn keys_sorted(&self) -> bool {
+        self.sections().iter().all(|(name, items)| {
+            let items = match self.ckb_address() {
+                Some(_) if *name == "addresses" => {
+                    let rest = &items[1..];
+                    if rest.binary_search_by(|data| data.key.as_str().cmp("ckb")).is_ok() {
+                        return false;
+                    }
+                    rest
+                }
+                _ => *items,
+            };
+            items.windows(2).all(|pair| pair[0].key < pair[1].key)
+        })
+    }
+
+    /// Entries keyed by a chain name must hold a valid address of that chain
+    pub fn verify_addresses(&self) -> Result<(), ChainAddressError> {
+        for (_, items) in self.sections() {
+            for item in items {
+                address::verify_entry(&item.key, &item.value)?;
+            }
         }
+        Ok(())
+    }
 
-        return true
+    /// Entries of `dweb` keyed by a known network must hold a well-formed identifier
+    pub fn verify_dweb(&self) -> Result<(), DwebError> {
+        for item in self.dweb.iter() {
+            dweb::verify_entry(&item.key, &item.value)?;
+        }
+        Ok(())
+    }
+
+    /// Entries must hold a known label allowed in their section
+    pub fn verify_labels(&self) -> Result<(), LabelError> {
+        for (name, items) in self.sections() {
+            for item in items {
+                label::verify_entry(name, item.label.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self) -> bool {
+        self.ckb_address().is_some()
+            && self.duplicate_key().is_none()
+            && self.verify_addresses().is_ok()
+            && self.verify_dweb().is_ok()
+            && self.verify_labels().is_ok()
     }
 }
@@ -0,0 +1,67 @@
+//! Labels of record entries and the rules the contract enforces for them.
+//!
+//! - `required`: the entry can never be removed, re-keyed or lose its label in an update, and
+//!   only the sections of `REQUIRED_SECTIONS` may hold it. The claim flow writes the "ckb"
+//!   address with it
+//! - `verified`: the value was attested off chain, an update changing the value must drop the
+//!   label
+//! - `public` and `private`: display hints for the dapp, with no rule on chain
+//!
+//! Entries without a label use the empty string.
+
+pub const LABEL_NONE: &str = "";
+pub const LABEL_REQUIRED: &str = "required";
+pub const LABEL_VERIFIED: &str = "verified";
+pub const LABEL_PUBLIC: &str = "public";
+pub const LABEL_PRIVATE: &str = "private";
+
+/// Sections allowed to hold `required` entries
+pub const REQUIRED_SECTIONS: [&str; 2] = ["profile", "addresses"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    None,
+    Required,
+    Verified,
+    Public,
+    Private,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelError {
+    UnknownLabel,
+    /// A `required` entry outside of `REQUIRED_SECTIONS`
+    RequiredNotAllowed,
+}
+
+impl Label {
+    pub fn parse(label: &[u8]) -> Result<Self, LabelError> {
+        match label {
+            b"" => Ok(Label::None),
+            b"required" => Ok(Label::Required),
+            b"verified" => Ok(Label::Verified),
+            b"public" => Ok(Label::Public),
+            b"private" => Ok(Label::Private),
+            _ => Err(LabelError::UnknownLabel),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Label::None => LABEL_NONE,
+            Label::Required => LABEL_REQUIRED,
+            Label::Verified => LABEL_VERIFIED,
+            Label::Public => LABEL_PUBLIC,
+            Label::Private => LABEL_PRIVATE,
+        }
+    }
+}
+
+/// Labels of an entry of the section `section`, as written by a transaction
+pub fn verify_entry(section: &str, label: &[u8]) -> Result<Label, LabelError> {
+    let label = Label::parse(label)?;
+    if label == Label::Required && !REQUIRED_SECTIONS.contains(&section) {
+        return Err(LabelError::RequiredNotAllowed);
+    }
+    Ok(label)
+}
@@ -1,44 +1,1152 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-#[cfg(not(feature = "std"))]
+#![no_std]
 extern crate alloc;
 extern crate no_std_compat as std;
 
-
+pub mod address;
+pub mod canonical_json;
+pub mod cell_data;
+pub mod config;
+pub mod diff;
+pub mod dweb;
 pub mod generated;
+pub mod hash;
+pub mod json_record;
+pub mod json_stream;
 pub mod kuai_mvp_data;
+pub mod label;
+pub mod operation;
+pub mod patch;
+pub mod path;
+pub mod record_reader;
+pub mod record_stream;
+pub mod since;
+pub mod storage;
+pub mod transition;
 
 #[cfg(test)]
 mod tests {
+    use alloc::format;
     use alloc::string::String;
+    use alloc::vec;
     use alloc::vec::Vec;
     use super::*;
     use crate::kuai_mvp_data::{Data, KuaiMvpView};
 
+    fn data(key: &str, value: &str, label: &str) -> Data {
+        Data {
+            key: String::from(key),
+            value: String::from(value),
+            label: String::from(label),
+        }
+    }
+
     #[test]
     fn it_works() {
-        let mut address_vec: Vec<Data> = Vec::new();
-
-        address_vec.push(Data {
-            key: String::from("123"),
-            value: String::from("123"),
-        });
-        address_vec.push(Data {
-            key: String::from("1234"),
-            value: String::from("1234"),
-        });
-        address_vec.push(Data {
-            key: String::from("12345"),
-            value: String::from("12345"),
-        });
+        let address_vec: Vec<Data> = vec![
+            data("123", "123", "required"),
+            data("1234", "1234", ""),
+            data("12345", "12345", ""),
+        ];
 
         let x = KuaiMvpView {
+            profile: vec![data("name", "kuai", "public")],
             addresses: address_vec,
+            custom: Vec::new(),
+            dweb: vec![data("ipfs", "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o", "")],
         };
 
         // println!("{:?}", x.as_molecule_data());
         let x1 = x.as_molecule_data().unwrap();
 
         let result = KuaiMvpView::new(x1.as_ref());
+        assert_eq!(result.unwrap(), x);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json_str = r#"{"addresses":[{"key":"ckb","value":"ckt1","label":"required"}],"dweb":[{"key":"ipfs","value":"Qm","label":""}]}"#;
+        let view: KuaiMvpView = serde_json::from_str(json_str).unwrap();
+
+        assert!(view.profile.is_empty());
+        assert_eq!(view.addresses[0].label, "required");
+        assert_eq!(serde_json::to_string(&view).unwrap(), json_str);
+
+        let legacy: KuaiMvpView = serde_json::from_str(r#"{"addresses":[{"key":"ckb","value":"ckt1"}]}"#).unwrap();
+        assert_eq!(legacy.addresses[0].label, "");
+    }
+
+    #[test]
+    fn json_record_matches_serde() {
+        use crate::json_record::{self, JsonRecordError};
+        use crate::kuai_mvp_data::ViewError;
+
+        let records = [
+            r#"{"addresses":[{"key":"ckb","value":"ckt1","label":"required"}]}"#,
+            r#" {"profile":[],"custom":[{"value":"caf\u00e9 \ud83d\ude00","key":"a\/b","x":[1,-2.5e3,null]}]} "#,
+            r#"{"dweb":[{"key":"ipfs","value":"é"}],"version":{"major":1,"minor":true}}"#,
+        ];
+        for record in records.iter() {
+            assert_eq!(
+                KuaiMvpView::from_json_slice(record.as_bytes()),
+                KuaiMvpView::as_json_str(record)
+            );
+        }
+
+        let invalid = [
+            (&br#"{"addresses":[{"key":"ckb""#[..], JsonRecordError::UnexpectedEnd),
+            (&br#"{"profile":[]} {}"#[..], JsonRecordError::TrailingData),
+            (&br#"{"profile":[],"profile":[]}"#[..], JsonRecordError::DuplicateSection),
+            (&br#"{"profile":[{"key":"a"}]}"#[..], JsonRecordError::MissingField),
+            (&br#"{"profile":[{"key":"a","key":"b","value":""}]}"#[..], JsonRecordError::DuplicateField),
+            (&br#"{"profile":[{"key":1,"value":""}]}"#[..], JsonRecordError::InvalidType),
+            (&br#"{"profile":null}"#[..], JsonRecordError::InvalidType),
+            (&br#"{"profile":[{"key":"\ud800","value":""}]}"#[..], JsonRecordError::InvalidString),
+            (&b"{\"profile\":[{\"key\":\"\t\",\"value\":\"\"}]}"[..], JsonRecordError::InvalidString),
+            (&br#"{"a":01}"#[..], JsonRecordError::UnexpectedByte),
+        ];
+        for (record, err) in invalid.iter() {
+            assert_eq!(json_record::validate(record), Err(*err));
+            assert_eq!(KuaiMvpView::from_json_slice(record), Err(ViewError::InvalidJson));
+            assert!(serde_json::from_slice::<KuaiMvpView>(record).is_err());
+        }
+
+        let mut deep = b"{\"x\":".to_vec();
+        deep.extend_from_slice(&[b'['; 200]);
+        assert_eq!(json_record::validate(&deep), Err(JsonRecordError::TooDeep));
+        assert_eq!(
+            KuaiMvpView::from_json_slice(b"{\"profile\":[{\"key\":\"\xff\"}]}"),
+            Err(ViewError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn json_stream_in_chunks() {
+        use crate::json_record::{self, JsonRecordError};
+        use crate::json_stream::{Span, Validator};
+        use crate::record_stream::RecordStream;
+
+        let records: [&[u8]; 16] = [
+            br#"{"addresses":[{"key":"ckb","value":"ckt1","label":"required"}]}"#,
+            br#" {"profile":[],"custom":[{"value":"caf\u00e9 \ud83d\ude00","key":"a\/b","x":[1,-2.5e3,{"y":null}]}]} "#,
+            "{\"dweb\":[{\"key\":\"ipfs\",\"value\":\"\u{e9}\"}],\"v\":{\"a\":[true,false,0.5E+2]}}".as_bytes(),
+            br#"{"prof\u0069le":[{"key":"","value":""}],"profile ":[]}"#,
+            br#"{"addresses":[{"key":"ckb""#,
+            br#"{"profile":[]} {}"#,
+            br#"{"profile":[],"profile":[]}"#,
+            br#"{"profile":[{"key":"a"}]}"#,
+            br#"{"profile":[{"key":"a","key":"b","value":""}]}"#,
+            br#"{"profile":[{"key":1,"value":""}]}"#,
+            br#"{"profile":[1]}"#,
+            br#"{"profile":[{"key":"\ud800","value":""}]}"#,
+            b"{\"profile\":[{\"key\":\"\t\",\"value\":\"\"}]}",
+            br#"{"a":01}"#,
+            br#"{"a":[1,]}"#,
+            b"{\"a\":1,\"b\":\"\xff\"} x",
+        ];
+
+        for record in records.iter() {
+            let mut expected = Vec::new();
+            let result = json_record::visit(record, |section, entry| {
+                let label = entry.label.map(|label| (label.raw(), label.len()));
+                expected.push((section, entry.key.raw(), entry.value.len(), label));
+            });
+
+            for chunk_size in [1, 2, 3, 7, record.len()] {
+                let mut validator = Validator::new();
+                let mut entries = Vec::new();
+                for chunk in record.chunks(chunk_size) {
+                    validator.feed(chunk, |section, entry| entries.push((section, entry)));
+                }
+                assert_eq!(validator.finish(), result);
+                if result.is_err() {
+                    continue;
+                }
+
+                let field = |span: Span| core::str::from_utf8(&record[span.start..span.end]);
+                let fields: Vec<_> = entries
+                    .iter()
+                    .map(|(section, entry)| {
+                        let label = entry.label.map(|label| (field(label).unwrap(), label.len));
+                        (*section, field(entry.key).unwrap(), entry.value.len, label)
+                    })
+                    .collect();
+                assert_eq!(fields, expected);
+            }
+
+            let mut view = KuaiMvpView::default();
+            let read = RecordStream::new(*record, record.len()).read_json(None, Some(&mut view));
+            assert_eq!(read.ok().map(|_| view), KuaiMvpView::from_json_slice(record).ok());
+        }
+
+        let mut deep = b"{\"x\":".to_vec();
+        deep.extend_from_slice(&[b'['; 200]);
+        let mut validator = Validator::new();
+        validator.feed(&deep, |_, _| {});
+        assert_eq!(validator.finish(), Err(JsonRecordError::TooDeep));
+    }
+
+    #[test]
+    fn strip_cell_data_prefix() {
+        use crate::cell_data::{strip_prefix, with_prefix, CellDataError};
+
+        let data = with_prefix(b"{}");
+        assert_eq!(strip_prefix(&data), Ok(&b"{}"[..]));
+        assert_eq!(strip_prefix(b"mvp-dap"), Err(CellDataError::TooShort));
+        assert_eq!(strip_prefix(b""), Err(CellDataError::TooShort));
+        assert_eq!(strip_prefix(b"mvp-dApp{}"), Err(CellDataError::PrefixMismatch));
+    }
+
+    #[test]
+    fn split_cell_data_format() {
+        use crate::cell_data::{split_format, CellDataError, DataFormat};
+
+        assert_eq!(split_format(b"{}"), Ok((DataFormat::Json, &b"{}"[..])));
+        assert_eq!(split_format(b"\x00{}"), Ok((DataFormat::Json, &b"{}"[..])));
+        assert_eq!(split_format(b"\x01\x04\x00\x00\x00"), Ok((DataFormat::Molecule, &b"\x04\x00\x00\x00"[..])));
+        assert_eq!(split_format(b""), Err(CellDataError::MissingFormat));
+        assert_eq!(split_format(b"\x02"), Err(CellDataError::UnknownFormat));
+    }
+
+    #[test]
+    fn config_limits() {
+        use crate::config::{Config, ConfigError, LimitError, SECTION_ADDRESSES};
+
+        assert_eq!(Config::from_args(&[]), Ok(Config::default()));
+        assert_eq!(Config::from_args(&[42]), Err(ConfigError::InvalidArgs));
+
+        let config = Config {
+            max_entries: 2,
+            max_key_length: 8,
+            max_value_length: 16,
+            max_data_size: 256,
+            allowed_sections: SECTION_ADDRESSES,
+            flags: 0,
+            lock_in_period: 0,
+        };
+        assert_eq!(Config::from_args(&config.as_args()), Ok(config));
+
+        let mut view = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "")],
+            ..Default::default()
+        };
+        assert_eq!(config.check_view(&view), Ok(()));
+        assert_eq!(config.check_data_size(257), Err(LimitError::DataTooLarge));
+
+        view.addresses[0].value = String::from("ckt1qzda0cr08m85hc8jlnfp3zer7xulejywt49kt2rr0vthywaa50xws");
+        assert_eq!(config.check_view(&view), Err(LimitError::ValueTooLong));
+
+        view.addresses[0].value = String::from("ckt1");
+        view.profile = view.addresses.clone();
+        assert_eq!(config.check_view(&view), Err(LimitError::SectionNotAllowed));
+    }
+
+    #[test]
+    fn config_type_id_args() {
+        use crate::config::{parse_args, type_id, Config, ConfigError, FLAG_TYPE_ID};
+        use crate::hash::blake2b::ckb_hash;
+
+        assert_eq!(ckb_hash(b"")[..4], [0x44, 0xf4, 0xc6, 0x97]);
+        let input = [7u8; 200];
+        let mut preimage = input.to_vec();
+        preimage.extend_from_slice(&3u64.to_le_bytes());
+        assert_eq!(type_id(&input, 3), ckb_hash(&preimage));
+
+        let config = Config::default();
+        assert_eq!(parse_args(&config.as_args()), Ok((config, None)));
+        assert_eq!(parse_args(&config.as_type_id_args(&[1; 32])), Err(ConfigError::InvalidArgs));
+
+        let config = Config {
+            flags: FLAG_TYPE_ID,
+            ..Default::default()
+        };
+        assert_eq!(parse_args(&config.as_type_id_args(&[1; 32])), Ok((config, Some([1; 32]))));
+        assert_eq!(parse_args(&config.as_args()), Err(ConfigError::InvalidArgs));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let mut view = KuaiMvpView {
+            addresses: vec![data("ckb", "v", ""), data("dot", "v", ""), data("sol", "v", "")],
+            custom: vec![data("a", "v", ""), data("b", "v", "")],
+            ..Default::default()
+        };
+        assert_eq!(view.duplicate_key(), None);
+        assert!(view.keys_sorted());
+        assert!(view.verify());
+
+        view.custom.push(data("a", "v", ""));
+        assert_eq!(view.duplicate_key(), Some(("custom", "a")));
+        assert!(!view.keys_sorted());
+        assert!(!view.verify());
+
+        view.custom.pop();
+        view.addresses.push(data("ckb", "v", ""));
+        assert_eq!(view.duplicate_key(), Some(("addresses", "ckb")));
+        assert!(!view.keys_sorted());
+
+        view.addresses = vec![data("ckb", "v", ""), data("sol", "v", ""), data("dot", "v", "")];
+        assert_eq!(view.duplicate_key(), None);
+        assert!(!view.keys_sorted());
+    }
+
+    #[test]
+    fn large_sections_need_sorted_keys() {
+        use crate::record_reader::{RecordReader, MAX_UNSORTED_ENTRIES};
+
+        let keys: Vec<String> = (0..=MAX_UNSORTED_ENTRIES).map(|i| format!("{:03}", i)).collect();
+        let mut view = KuaiMvpView {
+            custom: keys.iter().map(|key| data(key, "v", "")).collect(),
+            ..Default::default()
+        };
+        let bytes = view.as_molecule_data().unwrap();
+        let reader = RecordReader::new(&bytes, false).unwrap();
+        assert!(reader.needs_sorted_keys());
+        assert!(reader.keys_sorted());
+        assert_eq!(reader.duplicate_key(), None);
+
+        view.custom[1].key = keys[0].clone();
+        let bytes = view.as_molecule_data().unwrap();
+        let reader = RecordReader::new(&bytes, false).unwrap();
+        assert_eq!(reader.duplicate_key(), Some(("custom", keys[0].as_bytes())));
+
+        // unsorted, the duplicate is out of reach and `keys_sorted` rejects the record instead
+        view.custom.swap(1, MAX_UNSORTED_ENTRIES);
+        let bytes = view.as_molecule_data().unwrap();
+        let reader = RecordReader::new(&bytes, false).unwrap();
+        assert_eq!(reader.duplicate_key(), None);
+        assert!(!reader.keys_sorted());
+
+        view.custom.remove(2);
+        let bytes = view.as_molecule_data().unwrap();
+        let reader = RecordReader::new(&bytes, false).unwrap();
+        assert!(!reader.needs_sorted_keys());
+        assert_eq!(reader.duplicate_key(), Some(("custom", keys[0].as_bytes())));
+    }
+
+    #[test]
+    fn record_reader_matches_view() {
+        use crate::config::{Config, LimitError};
+        use crate::kuai_mvp_data::ViewError;
+        use crate::record_reader::RecordReader;
+
+        let views = [
+            KuaiMvpView::default(),
+            KuaiMvpView {
+                addresses: vec![
+                    data("ckb", "ckt1", ""),
+                    data("dot", "1", ""),
+                    data("sol", "2", ""),
+                ],
+                custom: vec![data("a", "x", ""), data("b", "yz", "")],
+                ..Default::default()
+            },
+            KuaiMvpView {
+                addresses: vec![
+                    data("ckb", "ckt1", ""),
+                    data("sol", "1", ""),
+                    data("ckb", "ckt1", ""),
+                ],
+                profile: vec![data("b", "", ""), data("a", "", ""), data("b", "", "")],
+                ..Default::default()
+            },
+            KuaiMvpView {
+                addresses: vec![data("eth", "0x52908400098527886E0F7030069857D2E4169EE7", "")],
+                dweb: vec![data("ipfs", "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG", "")],
+                ..Default::default()
+            },
+            KuaiMvpView {
+                addresses: vec![data("eth", "0x52908400098527886e0F7030069857D2E4169EE7", "")],
+                dweb: vec![data("ipfs", "Qm", "")],
+                ..Default::default()
+            },
+        ];
+        let limits = Config {
+            max_entries: 2,
+            max_value_length: 1,
+            ..Default::default()
+        };
+
+        for view in views.iter() {
+            let bytes = view.as_molecule_data().unwrap();
+            let reader = RecordReader::new(&bytes, false).unwrap();
+
+            assert_eq!(reader.to_view(), *view);
+            assert_eq!(reader.ckb_address(), view.ckb_address().map(str::as_bytes));
+            assert_eq!(
+                reader.duplicate_key(),
+                view.duplicate_key().map(|(name, key)| (name, key.as_bytes()))
+            );
+            assert_eq!(reader.keys_sorted(), view.keys_sorted());
+            assert_eq!(reader.verify_addresses(), view.verify_addresses());
+            assert_eq!(reader.verify_dweb(), view.verify_dweb());
+            assert_eq!(limits.check_reader(&reader), limits.check_view(view));
+        }
+
+        let bytes = views[1].as_molecule_data().unwrap();
+        let reader = RecordReader::new(&bytes, false).unwrap();
+        assert_eq!(limits.check_reader(&reader), Err(LimitError::TooManyEntries));
+        assert_eq!(RecordReader::new(&bytes[1..], true).err(), Some(ViewError::InvalidMolecule));
+
+    }
+
+    #[test]
+    fn record_stream_matches_reader() {
+        use crate::address::ChainAddressError;
+        use crate::cell_data::DataFormat;
+        use crate::config::{Config, LimitError, FLAG_CANONICAL_JSON, FLAG_SORTED_KEYS};
+        use crate::json_record::JsonRecordError;
+        use crate::label::LabelError;
+        use crate::record_reader::{RecordReader, MAX_UNSORTED_ENTRIES};
+        use crate::record_stream::{CkbAddress, RecordStream, StreamError};
+        use crate::record_stream::{VALUE_CAPACITY, WINDOW_SIZE};
+        use core::convert::Infallible;
+
+        type Result = core::result::Result<KuaiMvpView, StreamError<Infallible>>;
+        let read = |data: &[u8], format: DataFormat, rules: Option<&Config>| -> Result {
+            let mut view = KuaiMvpView::default();
+            RecordStream::new(data, data.len()).read(format, rules, Some(&mut view))?;
+            Ok(view)
+        };
+        // the error of a JSON record, which its molecule twin must share
+        let check = |view: &KuaiMvpView, config: &Config| {
+            let json = serde_json::to_string(view).unwrap();
+            let json = read(json.as_bytes(), DataFormat::Json, Some(config)).err();
+            let bytes = view.as_molecule_data().unwrap();
+            assert_eq!(read(&bytes, DataFormat::Molecule, Some(config)).err(), json);
+            json
+        };
+        let config = Config::default();
+
+        // escapes decoded across the windows of a record larger than one
+        let value = "caf\u{e9} \"\u{1f600}\"\\";
+        let view = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "required")],
+            custom: (0..WINDOW_SIZE / 16).map(|i| data(&format!("k{:03}", i), value, "")).collect(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&view)
+            .unwrap()
+            .replace('\u{e9}', "\\u00e9")
+            .replace('\u{1f600}', "\\ud83d\\ude00");
+        assert!(json.len() > WINDOW_SIZE);
+        assert_eq!(read(json.as_bytes(), DataFormat::Json, Some(&config)), Ok(view.clone()));
+        let bytes = view.as_molecule_data().unwrap();
+        assert_eq!(read(&bytes, DataFormat::Molecule, Some(&config)), Ok(view.clone()));
+        let mut stream = RecordStream::new(&bytes[..], bytes.len());
+        stream.read(DataFormat::Molecule, Some(&config), None).unwrap();
+        assert_eq!(stream.ckb_address(), CkbAddress::new("ckt1"));
+
+        // the layout and UTF-8 errors of `RecordReader::verified`
+        let mut invalid_utf8 = bytes.to_vec();
+        let position = invalid_utf8.windows(4).position(|w| w == b"ckt1").unwrap();
+        invalid_utf8[position] = 0xff;
+        for data in [&bytes[..0], &bytes[..6], &bytes[..10], &bytes[1..], &invalid_utf8] {
+            let err = RecordReader::verified(data, true).err().map(StreamError::Molecule);
+            assert!(err.is_some());
+            assert_eq!(read(data, DataFormat::Molecule, None).err(), err);
+        }
+
+        let duplicated = KuaiMvpView {
+            profile: vec![data("b", "", ""), data("a", "", ""), data("b", "", "")],
+            ..Default::default()
+        };
+        assert_eq!(check(&duplicated, &config), Some(StreamError::DuplicateKey));
+        let sorted = Config {
+            flags: FLAG_SORTED_KEYS,
+            ..Default::default()
+        };
+        assert_eq!(check(&duplicated, &sorted), Some(StreamError::UnsortedKeys));
+        let json = serde_json::to_string(&duplicated).unwrap();
+        assert_eq!(read(json.as_bytes(), DataFormat::Json, None), Ok(duplicated));
+
+        let mut view = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", ""), data("btc", "1", ""), data("eth", "2", "")],
+            ..Default::default()
+        };
+        assert!(matches!(
+            check(&view, &sorted),
+            Some(StreamError::Address(ChainAddressError::Btc(_)))
+        ));
+        view.addresses[1].value = "1".repeat(VALUE_CAPACITY + 1);
+        assert_eq!(check(&view, &sorted), Some(StreamError::Limit(LimitError::ValueTooLong)));
+        view.addresses.truncate(1);
+        view.addresses[0].label = String::from("verified ");
+        assert_eq!(check(&view, &sorted), Some(StreamError::Label(LabelError::UnknownLabel)));
+        view.addresses[0].label.clear();
+        view.addresses.push(data("ckb", "ckt1", ""));
+        assert_eq!(check(&view, &config), Some(StreamError::DuplicateKey));
+        assert_eq!(check(&view, &sorted), Some(StreamError::UnsortedKeys));
+
+        // sections too large for pairwise comparisons must be sorted
+        let mut view = KuaiMvpView {
+            custom: (0..=MAX_UNSORTED_ENTRIES)
+                .map(|i| data(&format!("{:03}", i), "", ""))
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(check(&view, &config), None);
+        view.custom.swap(1, MAX_UNSORTED_ENTRIES);
+        assert_eq!(check(&view, &config), Some(StreamError::UnsortedKeys));
+        view.custom.pop();
+        assert_eq!(check(&view, &config), None);
+
+        let canonical = Config {
+            flags: FLAG_CANONICAL_JSON,
+            ..Default::default()
+        };
+        let record = |data: &[u8], config: &Config| {
+            read(data, DataFormat::Json, Some(config)).err()
+        };
+        assert_eq!(record(b"{}", &canonical), None);
+        assert_eq!(record(b"{ }", &canonical), Some(StreamError::NonCanonicalJson));
+        assert_eq!(record(b"{", &canonical), Some(StreamError::NonCanonicalJson));
+        assert_eq!(record(b"{", &config), Some(StreamError::Json(JsonRecordError::UnexpectedEnd)));
+        let invalid_utf8 = b"{\"custom\":[{\"key\":\"\xff\",\"label\":\"\",\"value\":\"\"}]}";
+        let err = Some(StreamError::Json(JsonRecordError::InvalidUtf8));
+        assert_eq!(record(invalid_utf8, &canonical), err);
+    }
+
+    #[test]
+    fn eth_addresses() {
+        use crate::address::eth::{self, EthAddressError};
+        use crate::address::ChainAddressError;
+        use crate::hash::keccak::keccak256;
+
+        assert_eq!(keccak256(b"")[..4], [0xc5, 0xd2, 0x46, 0x01]);
+
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let bytes = eth::parse(address).unwrap();
+            assert_eq!(eth::to_checksum_address(&bytes), address);
+        }
+        assert!(eth::parse("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_ok());
+        assert_eq!(
+            eth::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(EthAddressError::InvalidChecksum)
+        );
+        assert_eq!(
+            eth::parse("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Err(EthAddressError::MissingPrefix)
+        );
+        assert_eq!(eth::parse("0x5aAeb605"), Err(EthAddressError::InvalidLength));
+        assert_eq!(
+            eth::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg"),
+            Err(EthAddressError::InvalidChar)
+        );
+
+        let view = KuaiMvpView {
+            addresses: vec![data("eth", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", "")],
+            ..Default::default()
+        };
+        assert_eq!(
+            view.verify_addresses(),
+            Err(ChainAddressError::Eth(EthAddressError::InvalidChecksum))
+        );
+    }
+
+    #[test]
+    fn btc_addresses() {
+        use crate::address::btc::{self, AddressType, BtcAddressError, Network};
+        use crate::hash::sha256::sha256;
+
+        assert_eq!(sha256(b"abc")[..4], [0xba, 0x78, 0x16, 0xbf]);
+        assert_eq!(sha256(&[b'a'; 1000])[..4], [0x41, 0xed, 0xec, 0xe4]);
+
+        let valid = [
+            ("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Network::Mainnet, AddressType::P2pkh),
+            ("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", Network::Mainnet, AddressType::P2sh),
+            ("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", Network::Testnet, AddressType::P2pkh),
+            ("2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc", Network::Testnet, AddressType::P2sh),
+            ("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", Network::Mainnet, AddressType::P2wpkh),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                Network::Testnet,
+                AddressType::P2wsh,
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                Network::Mainnet,
+                AddressType::P2tr,
+            ),
+            ("BC1SW50QGDZ25J", Network::Mainnet, AddressType::Segwit(16)),
+        ];
+        for (address, network, address_type) in valid {
+            assert_eq!(btc::parse(address), Ok((network, address_type)));
+        }
+
+        assert!(btc::parse("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3").is_err());
+        assert_eq!(
+            btc::parse("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"),
+            Err(BtcAddressError::VariantMismatch)
+        );
+        assert_eq!(
+            btc::parse("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh"),
+            Err(BtcAddressError::VariantMismatch)
+        );
+        assert_eq!(
+            btc::parse("BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P"),
+            Err(BtcAddressError::InvalidProgram)
+        );
+    }
+
+    #[test]
+    fn dweb_entries() {
+        use crate::dweb::{self, DwebError};
+
+        assert_eq!(dweb::parse_ipfs("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"), Ok(()));
+        assert_eq!(
+            dweb::parse_ipfs("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"),
+            Ok(())
+        );
+        assert_eq!(dweb::parse_ipfs("zdj7WWeQ43G6JJvLWQWZpyHuAMq6uYWRjkBXFad11vE2LHhQ7"), Ok(()));
+        assert_eq!(
+            dweb::parse_ipfs("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5O"),
+            Err(DwebError::InvalidEncoding)
+        );
+        assert_eq!(dweb::parse_ipfs("hello"), Err(DwebError::UnsupportedMultibase));
+
+        assert_eq!(
+            dweb::parse_ipns("12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA"),
+            Ok(())
+        );
+        assert_eq!(
+            dweb::parse_ipns("k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8"),
+            Ok(())
+        );
+        assert_eq!(
+            dweb::parse_ipns("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"),
+            Err(DwebError::InvalidKey)
+        );
+
+        assert_eq!(dweb::parse_arweave("bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U"), Ok(()));
+        assert_eq!(
+            dweb::parse_arweave("bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt+U"),
+            Err(DwebError::InvalidEncoding)
+        );
+
+        let reference = "c4b4c2b8a5c0e1f9d0a4b5e6f7081920a1b2c3d4e5f60718293a4b5c6d7e8f90";
+        assert_eq!(dweb::parse_swarm(reference), Ok(()));
+        assert_eq!(dweb::parse_swarm(&reference[1..]), Err(DwebError::InvalidLength));
+    }
+
+    #[test]
+    fn canonical_json() {
+        use crate::canonical_json::{self, is_canonical};
+
+        let view = KuaiMvpView {
+            profile: vec![data("name", "k\"u\u{1}a/i\u{e9}", "")],
+            addresses: vec![data("ckb", "ckt1", "required")],
+            ..Default::default()
+        };
+        let json_str = view.as_canonical_json().unwrap();
+        assert_eq!(
+            json_str,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1"}],"profile":[{"key":"name","label":"","value":"k\"u\u0001a/ié"}]}"#
+        );
+        assert!(is_canonical(json_str.as_bytes()));
+        assert_eq!(KuaiMvpView::as_json_str(&json_str), Ok(view));
+        assert!(is_canonical(b"{}"));
+
+        // the same record and entries in any other form
+        for json in [
+            r#" {"addresses":[{"key":"ckb","label":"required","value":"ckt1"}]}"#,
+            r#"{"addresses":[{"key":"ckb","value":"ckt1","label":"required"}]}"#,
+            r#"{"addresses":[{"key":"ckb","value":"ckt1"}]}"#,
+            r#"{"profile":[],"addresses":[{"key":"ckb","label":"required","value":"ckt1"}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1"}],"custom":[]}"#,
+            r#"{"profile":[{"key":"a","label":"","value":""}],"addresses":[{"key":"ckb","label":"required","value":"ckt1"}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1","x":1}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1"}],"x":1}"#,
+            r#"{"addresses":[{"key":"ckb", "label":"required","value":"ckt1"}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1\/"}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"
+"}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":""}]}"#,
+            r#"{"addresses":[{"key":"ckb","label":"required","value":"ckt1"}]"#,
+        ] {
+            assert!(!is_canonical(json.as_bytes()), "{}", json);
+        }
+        assert!(is_canonical(br#"{"custom":[{"key":"a","label":"","value":"\n\u001f"}]}"#));
+
+        // the number formatting of ECMAScript, with the shortest digits of the double
+        for (json, canonical) in [
+            ("[1.5,-0,100.0,1e21,1e20,1e-7,0.000001]", "[1.5,0,100,1e+21,100000000000000000000,1e-7,0.000001]"),
+            ("[123e-20,-1.7976931348623157e308,5e-324]", "[1.23e-18,-1.7976931348623157e+308,5e-324]"),
+            ("[12345678901234567890,0.1]", "[12345678901234567000,0.1]"),
+        ] {
+            let value: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(canonical_json::to_string(&value).unwrap(), canonical);
+        }
+        // members are ordered by UTF-16 code units, unlike the UTF-8 order of `BTreeMap`
+        assert_eq!(
+            canonical_json::to_string(&serde_json::json!({"\u{e000}": 1, "\u{1f600}": 2})).unwrap(),
+            "{\"\u{1f600}\":2,\"\u{e000}\":1}"
+        );
+    }
+
+    #[test]
+    fn update_transition_rules() {
+        use crate::operation::Operation;
+        use crate::transition::{verify_create, verify_update, TransitionError};
+
+        let old = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1old", "required"), data("eth", "0x", "required")],
+            ..Default::default()
+        };
+        assert_eq!(verify_create(old.ckb_address()), Ok(()));
+        assert_eq!(verify_create(None), Err(TransitionError::MissingCkbAddress));
+
+        let mut new = old.clone();
+        new.addresses[1].value = String::from("0x01");
+        assert_eq!(verify_update(&old, &new, Operation::Update), Ok(()));
+
+        new.addresses.pop();
+        assert_eq!(
+            verify_update(&old, &new, Operation::Update),
+            Err(TransitionError::RequiredEntryRemoved)
+        );
+
+        let mut transferred = old.clone();
+        transferred.addresses[0].value = String::from("ckt1new");
+        assert_eq!(
+            verify_update(&old, &transferred, Operation::Update),
+            Err(TransitionError::CkbAddressChanged)
+        );
+        assert_eq!(verify_update(&old, &transferred, Operation::Transfer), Ok(()));
+    }
+
+    #[test]
+    fn label_rules() {
+        use crate::label::{self, Label, LabelError};
+        use crate::operation::Operation;
+        use crate::record_reader::RecordReader;
+        use crate::transition::{verify_update, TransitionError};
+
+        for label in [Label::None, Label::Required, Label::Verified, Label::Public, Label::Private] {
+            assert_eq!(Label::parse(label.as_str().as_bytes()), Ok(label));
+        }
+        assert_eq!(Label::parse(b"Required"), Err(LabelError::UnknownLabel));
+        assert_eq!(label::verify_entry("profile", b"required"), Ok(Label::Required));
+        assert_eq!(
+            label::verify_entry("custom", b"required"),
+            Err(LabelError::RequiredNotAllowed)
+        );
+
+        let old = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "required")],
+            profile: vec![data("name", "kuai", "verified")],
+            ..Default::default()
+        };
+        assert_eq!(old.verify_labels(), Ok(()));
+
+        let mut new = old.clone();
+        new.dweb.push(data("ipfs", "Qm", "required"));
+        assert_eq!(new.verify_labels(), Err(LabelError::RequiredNotAllowed));
+        let molecule = new.as_molecule_data().unwrap();
+        let reader = RecordReader::new(&molecule, true).unwrap();
+        assert_eq!(reader.verify_labels(), Err(LabelError::RequiredNotAllowed));
+
+        // a required entry keeps its key and its label
+        let mut new = old.clone();
+        new.addresses[0].label = String::new();
+        assert_eq!(
+            verify_update(&old, &new, Operation::Update),
+            Err(TransitionError::RequiredEntryRemoved)
+        );
+
+        // a verified entry with a new value drops the label
+        let mut new = old.clone();
+        new.profile[0].value = String::from("kuai-js");
+        assert_eq!(
+            verify_update(&old, &new, Operation::Update),
+            Err(TransitionError::VerifiedValueChanged)
+        );
+        new.profile[0].label = String::from("public");
+        assert_eq!(verify_update(&old, &new, Operation::Update), Ok(()));
+    }
+
+    #[test]
+    fn destroy_transition_rules() {
+        use crate::config::{Config, FLAG_IMMUTABLE};
+        use crate::since;
+        use crate::transition::{verify_destroy, TransitionError};
+
+        assert_eq!(verify_destroy(&Config::default(), 0), Ok(()));
+
+        let immutable = Config {
+            flags: FLAG_IMMUTABLE,
+            ..Default::default()
+        };
+        assert_eq!(verify_destroy(&immutable, 0), Err(TransitionError::ImmutableRecord));
+
+        // relative epochs, 1 epoch and a half
+        let epoch = |number: u64, index: u64, length: u64| {
+            0xa000_0000_0000_0000 | number | (index << 24) | (length << 40)
+        };
+        let lock_in = Config {
+            lock_in_period: epoch(1, 1, 2),
+            ..Default::default()
+        };
+        assert_eq!(verify_destroy(&lock_in, 0), Err(TransitionError::LockInPeriod));
+        assert_eq!(
+            verify_destroy(&lock_in, epoch(1, 1, 4)),
+            Err(TransitionError::LockInPeriod)
+        );
+        assert_eq!(verify_destroy(&lock_in, epoch(1, 2, 4)), Ok(()));
+        assert!(since::reaches(epoch(2, 0, 0), lock_in.lock_in_period));
+        assert!(!since::reaches(0x8000_0000_0000_0002, lock_in.lock_in_period));
+    }
+
+    #[test]
+    fn decode_errors() {
+        use crate::kuai_mvp_data::ViewError;
+
+        assert_eq!(KuaiMvpView::new(&[1, 2, 3]), Err(ViewError::InvalidMolecule));
+        assert_eq!(KuaiMvpView::as_json_str("{\"addresses\":"), Err(ViewError::InvalidJson));
+
+        let x = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "")],
+            ..Default::default()
+        };
+        let mut bytes = x.as_molecule_data().unwrap().to_vec();
+        let position = bytes.windows(4).position(|w| w == b"ckt1").unwrap();
+        bytes[position] = 0xff;
+        assert_eq!(KuaiMvpView::new(&bytes), Err(ViewError::InvalidUtf8));
+    }
+
+    #[test]
+    fn record_diff() {
+        use crate::diff::Change;
+        use crate::json_record::Section;
+
+        let old = KuaiMvpView {
+            profile: vec![data("name", "kuai", ""), data("bio", "", "")],
+            addresses: vec![data("ckb", "ckt1", "required")],
+            ..Default::default()
+        };
+        assert_eq!(KuaiMvpView::diff(&old, &old), vec![]);
+
+        let new = KuaiMvpView {
+            profile: vec![data("avatar", "ipfs", ""), data("name", "kuai-js", "verified")],
+            addresses: vec![data("ckb", "ckt1", "required")],
+            ..Default::default()
+        };
+        let changes = KuaiMvpView::diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    section: Section::Profile,
+                    entry: data("bio", "", ""),
+                },
+                Change::Added {
+                    section: Section::Profile,
+                    entry: data("avatar", "ipfs", ""),
+                },
+                Change::ValueChanged {
+                    section: Section::Profile,
+                    key: String::from("name"),
+                    old: String::from("kuai"),
+                    new: String::from("kuai-js"),
+                },
+                Change::LabelChanged {
+                    section: Section::Profile,
+                    key: String::from("name"),
+                    old: String::new(),
+                    new: String::from("verified"),
+                },
+            ]
+        );
+
+        let json = serde_json::to_string(&changes[2..]).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"op":"valueChanged","section":"profile","key":"name","old":"kuai","new":"kuai-js"},{"op":"labelChanged","section":"profile","key":"name","old":"","new":"verified"}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<Change>>(&json).unwrap(), changes[2..]);
+    }
+
+    #[test]
+    fn record_patches() {
+        use crate::patch::{PatchError, PatchOperation};
+        use serde_json::json;
+
+        let view = KuaiMvpView {
+            profile: vec![data("name", "kuai", "")],
+            addresses: vec![data("ckb", "ckt1", "required")],
+            ..Default::default()
+        };
+
+        // sections are replaced as a whole and `null` empties them
+        let mut patched = view.clone();
+        patched
+            .merge_patch(&json!({
+                "profile": null,
+                "custom": [{"key": "bio", "value": "hi"}],
+            }))
+            .unwrap();
+        assert_eq!(patched.profile, vec![]);
+        assert_eq!(patched.custom, vec![data("bio", "hi", "")]);
+        assert_eq!(patched.addresses, view.addresses);
+
+        let mut patched = view.clone();
+        assert_eq!(
+            patched.merge_patch(&json!({"addresses": null})),
+            Err(PatchError::MissingCkbAddress)
+        );
+        assert_eq!(patched.merge_patch(&json!([])), Err(PatchError::InvalidValue));
+        assert_eq!(patched, view);
+
+        let operations: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "add", "path": "/profile/0", "value": {"key": "avatar", "value": "ipfs"}},
+            {"op": "replace", "path": "/profile/1/value", "value": "kuai-js"},
+            {"op": "add", "path": "/custom/-", "value": {"key": "a~/b", "value": ""}},
+            {"op": "replace", "path": "/custom/0/key", "value": "bio"},
+        ]))
+        .unwrap();
+        let mut patched = view.clone();
+        patched.apply_patch(&operations).unwrap();
+        assert_eq!(
+            patched.profile,
+            vec![data("avatar", "ipfs", ""), data("name", "kuai-js", "")]
+        );
+        assert_eq!(patched.custom, vec![data("bio", "", "")]);
+
+        let remove = |path: &str| PatchOperation::Remove {
+            path: String::from(path),
+        };
+        let mut patched = view.clone();
+        patched.apply_patch(&[remove("/profile/0")]).unwrap();
+        assert_eq!(patched.profile, vec![]);
+        assert_eq!(patched.apply_patch(&[remove("/profile/0")]), Err(PatchError::PathNotFound));
+        assert_eq!(patched.apply_patch(&[remove("/profile/-")]), Err(PatchError::InvalidPath));
+        assert_eq!(
+            patched.apply_patch(&[remove("/addresses/0/label")]),
+            Err(PatchError::InvalidPath)
+        );
+        assert_eq!(patched.apply_patch(&[remove("/data/0")]), Err(PatchError::InvalidPath));
+        assert_eq!(
+            patched.apply_patch(&[remove("/addresses/00")]),
+            Err(PatchError::InvalidPath)
+        );
+        assert_eq!(
+            patched.apply_patch(&[remove("/addresses/0")]),
+            Err(PatchError::MissingCkbAddress)
+        );
+        assert!(serde_json::from_value::<PatchOperation>(
+            json!({"op": "move", "from": "/profile/0", "path": "/custom/0"})
+        )
+        .is_err());
+
+        // an entry is moved by a `remove` and an `add`, checked once both are applied
+        let add = [PatchOperation::Add {
+            path: String::from("/custom/-"),
+            value: json!({"key": "name", "value": "kuai"}),
+        }];
+        let mut patched = view.clone();
+        assert_eq!(patched.apply_patch(&add), Ok(()));
+        assert_eq!(patched.apply_patch(&add), Err(PatchError::DuplicateKey));
+        let mut patched = view.clone();
+        let [add] = add;
+        patched.apply_patch(&[remove("/profile/0"), add]).unwrap();
+        assert_eq!(patched.custom, vec![data("name", "kuai", "")]);
+    }
+
+    #[test]
+    fn store_paths() {
+        use crate::path::{PathError, PathSegment, PathValue, StorePath};
+        use serde_json::json;
+
+        let path = |segments: &[PathSegment]| segments.to_vec();
+        let mut view = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "")],
+            ..Default::default()
+        };
+
+        // segments come as numbers or strings, like the paths lodash resolves
+        let value = path(&["data".into(), "addresses".into(), 0.into(), "value".into()]);
+        assert_eq!(view.get(&value), Ok(PathValue::Field("ckt1")));
+        let value_str = path(&["data".into(), "addresses".into(), "0".into(), "value".into()]);
+        assert_eq!(view.get(&value_str), Ok(PathValue::Field("ckt1")));
+        let from_json: StorePath = serde_json::from_str(r#"["data","addresses",0,"value"]"#)
+            .unwrap();
+        assert_eq!(from_json, value);
+        assert_eq!(
+            serde_json::to_value(view.get(&path(&["data".into(), "addresses".into()])).unwrap())
+                .unwrap(),
+            json!([{"key": "ckb", "value": "ckt1", "label": ""}])
+        );
+        assert_eq!(view.get(&path(&["data".into()])), Ok(PathValue::Record(&view)));
+
+        assert_eq!(
+            view.get(&path(&["state".into(), "addresses".into()])),
+            Err(PathError::InvalidPath)
+        );
+        assert_eq!(
+            view.get(&path(&["data".into(), "addresses".into(), "01".into()])),
+            Err(PathError::InvalidPath)
+        );
+        assert_eq!(
+            view.get(&path(&["data".into(), "addresses".into(), 0.into(), "name".into()])),
+            Err(PathError::InvalidPath)
+        );
+        assert_eq!(
+            view.get(&path(&["data".into(), "profile".into(), 0.into()])),
+            Err(PathError::PathNotFound)
+        );
+
+        view.set(&value, &json!("ckt1q")).unwrap();
+        assert_eq!(view.addresses, vec![data("ckb", "ckt1q", "")]);
+        assert_eq!(view.set(&value, &json!(1)), Err(PathError::InvalidValue));
+
+        // the entry right after the last one is appended, any later one has no parent
+        let entry = |index: usize| path(&["data".into(), "profile".into(), index.into()]);
+        view.set(&entry(0), &json!({"key": "name", "value": "kuai"})).unwrap();
+        assert_eq!(view.profile, vec![data("name", "kuai", "")]);
+        assert_eq!(
+            view.set(&entry(2), &json!({"key": "bio", "value": ""})),
+            Err(PathError::PathNotFound)
+        );
+        assert_eq!(view.set(&entry(1), &json!("bio")), Err(PathError::InvalidValue));
+
+        let custom = path(&["data".into(), "custom".into()]);
+        view.set(&custom, &json!([{"key": "bio", "value": "hi"}])).unwrap();
+        assert_eq!(view.custom, vec![data("bio", "hi", "")]);
+
+        view.remove(&entry(0)).unwrap();
+        assert_eq!(view.profile, vec![]);
+        assert_eq!(view.remove(&entry(0)), Err(PathError::PathNotFound));
+        view.remove(&custom).unwrap();
+        assert_eq!(view.custom, vec![]);
+        assert_eq!(view.remove(&value), Err(PathError::InvalidPath));
+        assert_eq!(view.remove(&path(&["data".into()])), Err(PathError::InvalidPath));
+    }
+
+    #[test]
+    fn chain_storage() {
+        use crate::cell_data::{split_format, strip_prefix, with_format, with_prefix, DataFormat};
+        use crate::storage::{
+            ChainStorage, JsonStorage, MoleculeStorage, Schema, MOLECULE_RECORD_SCHEMA,
+            RECORD_SCHEMA,
+        };
+
+        let view = KuaiMvpView {
+            addresses: vec![data("ckb", "ckt1", "required")],
+            custom: vec![data("bio", "caf\u{e9} \"kuai\"\n\u{1f}", "")],
+            ..Default::default()
+        };
+
+        // `JSON.stringify` of the same `StoreType['data']`
+        let json = JsonStorage.serialize(&view).unwrap();
+        assert_eq!(
+            json,
+            r#"{"addresses":[{"key":"ckb","value":"ckt1","label":"required"}],"custom":[{"key":"bio","value":"café \"kuai\"\n\u001f","label":""}]}"#
+                .as_bytes()
+        );
+        assert_eq!(JsonStorage.deserialize(&json), Ok(view.clone()));
+        assert!(ChainStorage::<KuaiMvpView>::deserialize(&JsonStorage, b"").is_err());
+
+        let molecule = MoleculeStorage::default().serialize(&view).unwrap();
+        assert_eq!(molecule, view.as_molecule_data().unwrap().to_vec());
+        assert_eq!(MoleculeStorage::default().clone_data(&view), Ok(view.clone()));
+
+        // `RecordModel` keeps the prefix of the input cell and replaces the rest
+        let data = with_prefix(&json);
+        assert_eq!(RECORD_SCHEMA.read(&data), json.as_slice());
+        assert_eq!(RECORD_SCHEMA.deserialize(&JsonStorage, &data), Ok(view.clone()));
+        let updated = RECORD_SCHEMA.serialize(&JsonStorage, &data, &KuaiMvpView::default()).unwrap();
+        assert_eq!(updated, with_prefix(b"{}"));
+        assert_eq!(RECORD_SCHEMA.init(b"{}"), [&[0u8; 8][..], b"{}"].concat());
+
+        // molecule records are read behind their format tag, the way the contract reads them
+        let storage = MoleculeStorage::default();
+        let data = MOLECULE_RECORD_SCHEMA.init(&molecule);
+        assert_eq!(data, [&[0u8; 9][..], &molecule].concat());
+        assert_eq!(MOLECULE_RECORD_SCHEMA.deserialize(&storage, &data), Ok(view.clone()));
+        let data = with_format(DataFormat::Molecule, &molecule);
+        let (format, record) = split_format(strip_prefix(&data).unwrap()).unwrap();
+        assert_eq!((format, record), (DataFormat::Molecule, molecule.as_slice()));
+        assert_eq!(MOLECULE_RECORD_SCHEMA.deserialize(&storage, &data), Ok(view.clone()));
+        let empty = KuaiMvpView::default();
+        let updated = MOLECULE_RECORD_SCHEMA.serialize(&storage, &data, &empty).unwrap();
+        let (format, record) = split_format(strip_prefix(&updated).unwrap()).unwrap();
+        assert_eq!(format, DataFormat::Molecule);
+        assert_eq!(storage.deserialize(record), Ok(empty));
+        assert!(RECORD_SCHEMA.deserialize(&storage, &data).is_err());
+
+        let field = Schema {
+            offset: 2,
+            length: 3,
+        };
+        assert_eq!(field.read(b"abcdefg"), b"cde");
+        assert_eq!(field.read(b"abc"), b"c");
+        assert_eq!(field.read(b"a"), b"");
+        assert_eq!(field.write(b"abcdefg", b"XY"), b"abXYfg");
+        assert_eq!(field.write(b"a", b"XY"), b"aXY");
+    }
+
+    #[test]
+    fn molecule_error_location() {
+        use crate::json_record::Section;
+        use crate::kuai_mvp_data::ViewError;
+        use crate::record_reader::{Field, MoleculeError};
+        use alloc::string::ToString;
+        use molecule::{pack_number, unpack_number};
+
+        let view = KuaiMvpView {
+            profile: vec![data("name", "kuai", "")],
+            addresses: vec![data("ckb", "ckt1", ""), data("eth", "0x", "")],
+            ..Default::default()
+        };
+        let bytes = view.as_molecule_data().unwrap().to_vec();
+        assert_eq!(KuaiMvpView::from_molecule_verified(&bytes, false), Ok(view.clone()));
+        assert_eq!(
+            KuaiMvpView::from_molecule_verified(&bytes[..bytes.len() - 1], true),
+            Err(MoleculeError::InvalidRecord)
+        );
+
+        let mut invalid_utf8 = bytes.clone();
+        let position = invalid_utf8.windows(2).position(|w| w == b"0x").unwrap();
+        invalid_utf8[position] = 0xff;
+        let err = KuaiMvpView::from_molecule_verified(&invalid_utf8, true).unwrap_err();
+        assert_eq!(
+            err,
+            MoleculeError::InvalidUtf8 {
+                section: Section::Addresses,
+                index: 1,
+                field: Field::Value,
+            }
+        );
+        assert_eq!(err.to_string(), "addresses[1].value: invalid UTF-8");
+        assert_eq!(KuaiMvpView::new(&invalid_utf8), Err(ViewError::InvalidUtf8));
+
+        // the length of the `MString` of the key of the first address runs past its end
+        let mut broken_field = bytes.clone();
+        let position = broken_field.windows(3).position(|w| w == b"ckb").unwrap();
+        broken_field[position - 4..position].copy_from_slice(&pack_number(4));
+        let err = KuaiMvpView::from_molecule_verified(&broken_field, true).unwrap_err();
+        assert_eq!(
+            err,
+            MoleculeError::InvalidField {
+                section: Section::Addresses,
+                index: 0,
+                field: Field::Key,
+            }
+        );
+        assert_eq!(err.to_string(), "addresses[0].key: invalid string");
+        assert_eq!(KuaiMvpView::new(&broken_field), Err(ViewError::InvalidMolecule));
 
+        // the total size of the entry vector of the profile
+        let mut broken_section = bytes;
+        let offset = unpack_number(&broken_section[4..8]) as usize;
+        broken_section[offset..offset + 4].copy_from_slice(&pack_number(1));
+        assert_eq!(
+            KuaiMvpView::from_molecule_verified(&broken_section, true),
+            Err(MoleculeError::InvalidSection(Section::Profile))
+        );
     }
 }
@@ -0,0 +1,32 @@
+/// Operation declared by a record input in the `input_type` field of its witness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Default operation, the record stays with its owner
+    Update,
+    /// Hand the record over to the lock of its paired output
+    Transfer,
+}
+
+pub const OPERATION_UPDATE: u8 = 0;
+pub const OPERATION_TRANSFER: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownOperation;
+
+impl Operation {
+    /// An absent or empty `input_type` means `Update`
+    pub fn from_input_type(input_type: Option<&[u8]>) -> Result<Self, UnknownOperation> {
+        match input_type {
+            None | Some([]) | Some([OPERATION_UPDATE]) => Ok(Operation::Update),
+            Some([OPERATION_TRANSFER]) => Ok(Operation::Transfer),
+            _ => Err(UnknownOperation),
+        }
+    }
+
+    pub fn as_input_type(&self) -> [u8; 1] {
+        match self {
+            Operation::Update => [OPERATION_UPDATE],
+            Operation::Transfer => [OPERATION_TRANSFER],
+        }
+    }
+}
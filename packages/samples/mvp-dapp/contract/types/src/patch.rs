@@ -0,0 +1,224 @@
+//! Partial updates of records: RFC 7396 merge patches, and the `add`, `remove` and `replace`
+//! operations of RFC 6902 on the entries of the sections.
+//!
+//! A patch is applied to a copy of the record, which must then pass the same rules as a new
+//! record (a leading "ckb" address, unique keys, well-formed addresses, dweb entries and
+//! labels) before it replaces the record.
+
+use crate::address::ChainAddressError;
+use crate::dweb::DwebError;
+use crate::json_record::Section;
+use crate::kuai_mvp_data::{Data, KuaiMvpView};
+use crate::label::LabelError;
+use crate::path::{field_mut, section_mut};
+use crate::record_reader::Field;
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    /// A path outside of the entries of the sections, or an operation it does not support
+    InvalidPath,
+    /// An index past the entries of the section
+    PathNotFound,
+    /// A value which is not an entry or a string where one is expected, or a merge patch
+    /// turning the record into something else than `StoreType['data']`
+    InvalidValue,
+    MissingCkbAddress,
+    DuplicateKey,
+    Address(ChainAddressError),
+    Dweb(DwebError),
+    Label(LabelError),
+}
+
+impl From<ChainAddressError> for PatchError {
+    fn from(err: ChainAddressError) -> Self {
+        PatchError::Address(err)
+    }
+}
+
+impl From<DwebError> for PatchError {
+    fn from(err: DwebError) -> Self {
+        PatchError::Dweb(err)
+    }
+}
+
+impl From<LabelError> for PatchError {
+    fn from(err: LabelError) -> Self {
+        PatchError::Label(err)
+    }
+}
+
+/// An operation of a JSON patch, `path` is a JSON pointer such as `/addresses/1`,
+/// `/addresses/-` or `/addresses/1/value`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Position of an entry, `None` is the end of the section, only valid for `add`
+struct Pointer {
+    section: Section,
+    index: Option<usize>,
+    field: Option<Field>,
+}
+
+/// A reference token of RFC 6901, with `~1` and `~0` unescaped
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn parse_index(token: &str) -> Result<Option<usize>, PatchError> {
+    if token == "-" {
+        return Ok(None);
+    }
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return Err(PatchError::InvalidPath);
+    }
+    token
+        .parse()
+        .map(Some)
+        .map_err(|_| PatchError::InvalidPath)
+}
+
+fn parse_pointer(path: &str) -> Result<Pointer, PatchError> {
+    let mut tokens = path
+        .strip_prefix('/')
+        .ok_or(PatchError::InvalidPath)?
+        .split('/')
+        .map(unescape);
+
+    let section = tokens.next().ok_or(PatchError::InvalidPath)?;
+    let section = Section::from_name(&section).ok_or(PatchError::InvalidPath)?;
+    let index = parse_index(&tokens.next().ok_or(PatchError::InvalidPath)?)?;
+    let field = match tokens.next() {
+        Some(name) => Some(Field::from_name(&name).ok_or(PatchError::InvalidPath)?),
+        None => None,
+    };
+    if tokens.next().is_some() || (field.is_some() && index.is_none()) {
+        return Err(PatchError::InvalidPath);
+    }
+
+    Ok(Pointer {
+        section,
+        index,
+        field,
+    })
+}
+
+fn to_entry(value: &Value) -> Result<Data, PatchError> {
+    Data::deserialize(value).map_err(|_| PatchError::InvalidValue)
+}
+
+fn to_string(value: &Value) -> Result<String, PatchError> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or(PatchError::InvalidValue)
+}
+
+fn apply_operation(view: &mut KuaiMvpView, operation: &PatchOperation) -> Result<(), PatchError> {
+    let (path, value) = match operation {
+        PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+            (path, Some(value))
+        }
+        PatchOperation::Remove { path } => (path, None),
+    };
+    let pointer = parse_pointer(path)?;
+    let items = section_mut(view, pointer.section);
+
+    // `add` inserts entries, or sets a field like `replace` as fields always exist
+    let adds_entry = matches!(operation, PatchOperation::Add { .. }) && pointer.field.is_none();
+    let index = match pointer.index {
+        Some(index) if index < items.len() || (adds_entry && index == items.len()) => index,
+        Some(_) => return Err(PatchError::PathNotFound),
+        None if adds_entry => items.len(),
+        None => return Err(PatchError::InvalidPath),
+    };
+
+    match (value, pointer.field) {
+        (Some(value), None) if adds_entry => items.insert(index, to_entry(value)?),
+        (Some(value), None) => items[index] = to_entry(value)?,
+        (Some(value), Some(field)) => *field_mut(&mut items[index], field) = to_string(value)?,
+        (None, None) => {
+            items.remove(index);
+        }
+        // entries always have their three fields
+        (None, Some(_)) => return Err(PatchError::InvalidPath),
+    }
+
+    Ok(())
+}
+
+/// RFC 7396 on JSON values: objects are merged member by member, `null` removes a member and
+/// anything else, arrays included, replaces the target
+fn merge(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Rules of a new record, see the module documentation
+fn verify(view: &KuaiMvpView) -> Result<(), PatchError> {
+    if view.ckb_address().is_none() {
+        return Err(PatchError::MissingCkbAddress);
+    }
+    if view.duplicate_key().is_some() {
+        return Err(PatchError::DuplicateKey);
+    }
+    view.verify_addresses()?;
+    view.verify_dweb()?;
+    view.verify_labels()?;
+    Ok(())
+}
+
+/// Sections are replaced as a whole, as arrays are in RFC 7396, and a `null` section is
+/// emptied
+pub fn merge_patch(view: &mut KuaiMvpView, patch: &Value) -> Result<(), PatchError> {
+    let mut target = serde_json::to_value(&*view).map_err(|_| PatchError::InvalidValue)?;
+    merge(&mut target, patch);
+    // a struct would also be read from an array
+    if !target.is_object() {
+        return Err(PatchError::InvalidValue);
+    }
+    let patched = KuaiMvpView::deserialize(&target).map_err(|_| PatchError::InvalidValue)?;
+
+    verify(&patched)?;
+    *view = patched;
+    Ok(())
+}
+
+/// The operations are applied in order, the record is only checked once all of them are, so
+/// an entry can be moved with a `remove` and an `add`
+pub fn apply_patch(view: &mut KuaiMvpView, patch: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut patched = view.clone();
+    for operation in patch {
+        apply_operation(&mut patched, operation)?;
+    }
+
+    verify(&patched)?;
+    *view = patched;
+    Ok(())
+}
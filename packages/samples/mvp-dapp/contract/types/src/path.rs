@@ -0,0 +1,212 @@
+//! Values of a record addressed by the `StorePath`s of `packages/models/src/store`, such as
+//! `['data', 'addresses', 0, 'value']`.
+//!
+//! A path starts with the storage location of the record, `data`, then names a section, the
+//! index of an entry and one of its fields. Indexes are numbers or their decimal strings, as
+//! the TS store passes every segment to lodash `get` as a string.
+
+use crate::json_record::Section;
+use crate::kuai_mvp_data::{Data, KuaiMvpView};
+use crate::record_reader::Field;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// `StorageLocation` the record is stored at
+pub const LOCATION_DATA: &str = "data";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PathSegment {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+impl From<&str> for PathSegment {
+    fn from(name: &str) -> Self {
+        PathSegment::Name(String::from(name))
+    }
+}
+
+impl PathSegment {
+    fn name(&self) -> Option<&str> {
+        match self {
+            PathSegment::Name(name) => Some(name),
+            PathSegment::Index(_) => None,
+        }
+    }
+
+    /// Canonical array indexes of JavaScript, `"01"` is a property name
+    fn index(&self) -> Option<usize> {
+        match self {
+            PathSegment::Index(index) => Some(*index),
+            PathSegment::Name(name) if name == "0" => Some(0),
+            PathSegment::Name(name) if name.starts_with('0') => None,
+            PathSegment::Name(name) if name.bytes().all(|b| b.is_ascii_digit()) => {
+                name.parse().ok()
+            }
+            PathSegment::Name(_) => None,
+        }
+    }
+}
+
+pub type StorePath = Vec<PathSegment>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// A path outside of the record, or naming something a record does not have
+    InvalidPath,
+    /// An index past the entries of the section
+    PathNotFound,
+    /// A value of another type than the one at the path
+    InvalidValue,
+}
+
+/// What a path points at, from the record down to a field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Record,
+    Section(Section),
+    Entry(Section, usize),
+    Field(Section, usize, Field),
+}
+
+fn parse(path: &[PathSegment]) -> Result<Target, PathError> {
+    let section = |segment: &PathSegment| {
+        segment
+            .name()
+            .and_then(Section::from_name)
+            .ok_or(PathError::InvalidPath)
+    };
+    let index = |segment: &PathSegment| segment.index().ok_or(PathError::InvalidPath);
+    let field = |segment: &PathSegment| {
+        segment
+            .name()
+            .and_then(Field::from_name)
+            .ok_or(PathError::InvalidPath)
+    };
+
+    match path {
+        [location, rest @ ..] if location.name() == Some(LOCATION_DATA) => match rest {
+            [] => Ok(Target::Record),
+            [s] => Ok(Target::Section(section(s)?)),
+            [s, i] => Ok(Target::Entry(section(s)?, index(i)?)),
+            [s, i, f] => Ok(Target::Field(section(s)?, index(i)?, field(f)?)),
+            _ => Err(PathError::InvalidPath),
+        },
+        _ => Err(PathError::InvalidPath),
+    }
+}
+
+/// Value at a path, serialized as the TS store holds it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum PathValue<'a> {
+    Record(&'a KuaiMvpView),
+    Section(&'a [Data]),
+    Entry(&'a Data),
+    Field(&'a str),
+}
+
+fn section_ref(view: &KuaiMvpView, section: Section) -> &Vec<Data> {
+    match section {
+        Section::Profile => &view.profile,
+        Section::Addresses => &view.addresses,
+        Section::Custom => &view.custom,
+        Section::Dweb => &view.dweb,
+    }
+}
+
+pub(crate) fn section_mut(view: &mut KuaiMvpView, section: Section) -> &mut Vec<Data> {
+    match section {
+        Section::Profile => &mut view.profile,
+        Section::Addresses => &mut view.addresses,
+        Section::Custom => &mut view.custom,
+        Section::Dweb => &mut view.dweb,
+    }
+}
+
+pub(crate) fn field_mut(entry: &mut Data, field: Field) -> &mut String {
+    match field {
+        Field::Key => &mut entry.key,
+        Field::Value => &mut entry.value,
+        Field::Label => &mut entry.label,
+    }
+}
+
+fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, PathError> {
+    T::deserialize(value).map_err(|_| PathError::InvalidValue)
+}
+
+pub fn get<'a>(view: &'a KuaiMvpView, path: &[PathSegment]) -> Result<PathValue<'a>, PathError> {
+    let entry = |section: Section, index: usize| {
+        section_ref(view, section)
+            .get(index)
+            .ok_or(PathError::PathNotFound)
+    };
+
+    Ok(match parse(path)? {
+        Target::Record => PathValue::Record(view),
+        Target::Section(section) => PathValue::Section(section_ref(view, section)),
+        Target::Entry(section, index) => PathValue::Entry(entry(section, index)?),
+        Target::Field(section, index, field) => {
+            let entry = entry(section, index)?;
+            PathValue::Field(match field {
+                Field::Key => &entry.key,
+                Field::Value => &entry.value,
+                Field::Label => &entry.label,
+            })
+        }
+    })
+}
+
+/// Same as `Store.set`: what holds the value must exist, and setting the entry right after
+/// the last one of a section appends it
+pub fn set(view: &mut KuaiMvpView, path: &[PathSegment], value: &Value) -> Result<(), PathError> {
+    match parse(path)? {
+        Target::Record => *view = from_value(value)?,
+        Target::Section(section) => *section_mut(view, section) = from_value(value)?,
+        Target::Entry(section, index) => {
+            let entry = from_value(value)?;
+            let items = section_mut(view, section);
+            match index.cmp(&items.len()) {
+                Ordering::Less => items[index] = entry,
+                Ordering::Equal => items.push(entry),
+                Ordering::Greater => return Err(PathError::PathNotFound),
+            }
+        }
+        Target::Field(section, index, field) => {
+            let text = value.as_str().ok_or(PathError::InvalidValue)?;
+            let entry = section_mut(view, section)
+                .get_mut(index)
+                .ok_or(PathError::PathNotFound)?;
+            *field_mut(entry, field) = String::from(text);
+        }
+    }
+    Ok(())
+}
+
+/// Entries are removed from their section and a removed section is emptied. The record and
+/// the fields of an entry are always there
+pub fn remove(view: &mut KuaiMvpView, path: &[PathSegment]) -> Result<(), PathError> {
+    match parse(path)? {
+        Target::Section(section) => section_mut(view, section).clear(),
+        Target::Entry(section, index) => {
+            let items = section_mut(view, section);
+            if index >= items.len() {
+                return Err(PathError::PathNotFound);
+            }
+            items.remove(index);
+        }
+        Target::Record | Target::Field(..) => return Err(PathError::InvalidPath),
+    }
+    Ok(())
+}
@@ -0,0 +1,407 @@
+//! Checks of molecule records run over the borrowed `KuaiMvpDataReader`.
+//!
+//! Entries are read as slices of the cell data, nothing is copied, so the rules of
+//! `KuaiMvpView` can run on large records without allocation.
+
+use crate::address::{self, ChainAddressError};
+use crate::dweb::{self, DwebError};
+use crate::generated::basic::MStringReader;
+use crate::generated::mvp_data::{KuaiMvpDataReader, ObjReader, ObjsReader};
+use crate::json_record::Section;
+use crate::kuai_mvp_data::{Data, KuaiMvpView, ViewError};
+use crate::label::{self, LabelError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{fmt, str};
+use molecule::prelude::Reader;
+use molecule::{unpack_number, NUMBER_SIZE};
+
+/// Largest section `RecordReader::duplicate_key` compares pairwise. Larger sections must have
+/// sorted keys, as with `FLAG_SORTED_KEYS`
+pub const MAX_UNSORTED_ENTRIES: usize = 64;
+
+/// Field of an entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Key,
+    Value,
+    Label,
+}
+
+impl Field {
+    pub const ALL: [Field; 3] = [Field::Key, Field::Value, Field::Label];
+
+    pub fn from_name(name: &str) -> Option<Field> {
+        Field::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Field::Key => "key",
+            Field::Value => "value",
+            Field::Label => "label",
+        }
+    }
+}
+
+/// Part of a molecule record failing `RecordReader::verified`, from the outermost
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoleculeError {
+    /// The header of the `KuaiMvpData` table, or fields unknown to this crate in strict mode
+    InvalidRecord,
+    /// The `Objs` vector of a section
+    InvalidSection(Section),
+    /// The `Obj` table of an entry
+    InvalidEntry { section: Section, index: usize },
+    /// The `MString` of a field
+    InvalidField {
+        section: Section,
+        index: usize,
+        field: Field,
+    },
+    InvalidUtf8 {
+        section: Section,
+        index: usize,
+        field: Field,
+    },
+}
+
+impl fmt::Display for MoleculeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoleculeError::InvalidRecord => write!(f, "invalid record table"),
+            MoleculeError::InvalidSection(section) => {
+                write!(f, "{}: invalid entry vector", section.name())
+            }
+            MoleculeError::InvalidEntry { section, index } => {
+                write!(f, "{}[{}]: invalid entry table", section.name(), index)
+            }
+            MoleculeError::InvalidField {
+                section,
+                index,
+                field,
+            } => write!(f, "{}[{}].{}: invalid string", section.name(), index, field.name()),
+            MoleculeError::InvalidUtf8 {
+                section,
+                index,
+                field,
+            } => write!(f, "{}[{}].{}: invalid UTF-8", section.name(), index, field.name()),
+        }
+    }
+}
+
+impl From<MoleculeError> for ViewError {
+    fn from(err: MoleculeError) -> Self {
+        match err {
+            MoleculeError::InvalidUtf8 { .. } => ViewError::InvalidUtf8,
+            _ => ViewError::InvalidMolecule,
+        }
+    }
+}
+
+/// An entry of a record, as the raw bytes of its `MString`s
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry<'r> {
+    pub key: &'r [u8],
+    pub value: &'r [u8],
+    pub label: &'r [u8],
+}
+
+impl<'r> Entry<'r> {
+    pub fn field(&self, field: Field) -> &'r [u8] {
+        match field {
+            Field::Key => self.key,
+            Field::Value => self.value,
+            Field::Label => self.label,
+        }
+    }
+
+    fn from_reader(obj: ObjReader<'r>) -> Self {
+        Entry {
+            key: obj.key().raw_data(),
+            value: obj.value().raw_data(),
+            label: obj.label().raw_data(),
+        }
+    }
+
+    /// Fields are checked by `RecordReader::new`, so this never fails on its entries
+    fn as_strs(&self) -> Option<(&'r str, &'r str)> {
+        Some((str::from_utf8(self.key).ok()?, str::from_utf8(self.value).ok()?))
+    }
+}
+
+/// Entries of a section, in order
+pub struct Entries<'r> {
+    objs: ObjsReader<'r>,
+    index: usize,
+    len: usize,
+}
+
+impl<'r> Entries<'r> {
+    pub fn new(objs: ObjsReader<'r>) -> Self {
+        Entries {
+            objs,
+            index: 0,
+            len: objs.len(),
+        }
+    }
+}
+
+impl<'r> Iterator for Entries<'r> {
+    type Item = Entry<'r>;
+
+    fn next(&mut self) -> Option<Entry<'r>> {
+        if self.index >= self.len {
+            return None;
+        }
+        let obj = self.objs.get_unchecked(self.index);
+        self.index += 1;
+        Some(Entry::from_reader(obj))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.len - self.index;
+        (rest, Some(rest))
+    }
+}
+
+impl<'r> ExactSizeIterator for Entries<'r> {}
+
+/// Number of items of a table or a dynvec, `None` when its header is broken
+fn item_count(slice: &[u8]) -> Option<usize> {
+    let number = |index: usize| {
+        slice
+            .get(index * NUMBER_SIZE..(index + 1) * NUMBER_SIZE)
+            .map(|bytes| unpack_number(bytes) as usize)
+    };
+
+    if number(0)? != slice.len() {
+        return None;
+    }
+    if slice.len() == NUMBER_SIZE {
+        return Some(0);
+    }
+    let offset_first = number(1)?;
+    if offset_first % NUMBER_SIZE != 0 || offset_first < NUMBER_SIZE * 2 {
+        return None;
+    }
+    let count = offset_first / NUMBER_SIZE - 1;
+    let mut previous = offset_first;
+    for index in 2..=count + 1 {
+        let offset = if index == count + 1 { slice.len() } else { number(index)? };
+        if offset < previous || offset > slice.len() {
+            return None;
+        }
+        previous = offset;
+    }
+    Some(count)
+}
+
+/// Item `index` of a table or a dynvec whose header passed `item_count`
+fn item(slice: &[u8], index: usize, count: usize) -> &[u8] {
+    let offset = |index: usize| {
+        if index == count {
+            return slice.len();
+        }
+        let start = (index + 1) * NUMBER_SIZE;
+        unpack_number(&slice[start..start + NUMBER_SIZE]) as usize
+    };
+    &slice[offset(index)..offset(index + 1)]
+}
+
+/// The outermost part of `data` failing `KuaiMvpDataReader::verify`
+fn locate_layout_error(data: &[u8], compatible: bool) -> MoleculeError {
+    let fits = |count: usize, field_count: usize| {
+        count == field_count || (compatible && count > field_count)
+    };
+
+    let count = match item_count(data) {
+        Some(count) if fits(count, KuaiMvpDataReader::FIELD_COUNT) => count,
+        _ => return MoleculeError::InvalidRecord,
+    };
+    for (section_index, section) in Section::ALL.into_iter().enumerate() {
+        let objs = item(data, section_index, count);
+        let len = match item_count(objs) {
+            Some(len) => len,
+            None => return MoleculeError::InvalidSection(section),
+        };
+        for index in 0..len {
+            let obj = item(objs, index, len);
+            let field_count = match item_count(obj) {
+                Some(field_count) if fits(field_count, ObjReader::FIELD_COUNT) => field_count,
+                _ => return MoleculeError::InvalidEntry { section, index },
+            };
+            for (field_index, field) in Field::ALL.into_iter().enumerate() {
+                let mstring = item(obj, field_index, field_count);
+                if MStringReader::verify(mstring, compatible).is_err() {
+                    return MoleculeError::InvalidField {
+                        section,
+                        index,
+                        field,
+                    };
+                }
+            }
+        }
+    }
+
+    // the checks above cover the whole layout
+    MoleculeError::InvalidRecord
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RecordReader<'r>(KuaiMvpDataReader<'r>);
+
+impl<'r> RecordReader<'r> {
+    /// Same checks as `KuaiMvpView::from_molecule`: the layout with `KuaiMvpDataReader::verify`
+    /// and every key, value and label as UTF-8
+    pub fn new(data: &'r [u8], compatible: bool) -> Result<Self, ViewError> {
+        Ok(Self::verified(data, compatible)?)
+    }
+
+    /// Same as `new` with the entry and the field that failed. Fields unknown to this crate
+    /// are accepted when `compatible` is set, as in `KuaiMvpDataReader::verify`
+    pub fn verified(data: &'r [u8], compatible: bool) -> Result<Self, MoleculeError> {
+        if KuaiMvpDataReader::verify(data, compatible).is_err() {
+            return Err(locate_layout_error(data, compatible));
+        }
+        let record = RecordReader(KuaiMvpDataReader::new_unchecked(data));
+
+        for (section, (_, entries)) in Section::ALL.into_iter().zip(record.sections()) {
+            for (index, entry) in entries.enumerate() {
+                for field in Field::ALL {
+                    if str::from_utf8(entry.field(field)).is_err() {
+                        return Err(MoleculeError::InvalidUtf8 {
+                            section,
+                            index,
+                            field,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(record)
+    }
+
+    pub fn as_reader(&self) -> KuaiMvpDataReader<'r> {
+        self.0
+    }
+
+    /// Sections in the order of `KuaiMvpView::sections`
+    pub fn sections(&self) -> [(&'static str, Entries<'r>); 4] {
+        [
+            ("profile", Entries::new(self.0.profile())),
+            ("addresses", Entries::new(self.0.addresses())),
+            ("custom", Entries::new(self.0.custom())),
+            ("dweb", Entries::new(self.0.dweb())),
+        ]
+    }
+
+    /// Same as `KuaiMvpView::ckb_address`
+    pub fn ckb_address(&self) -> Option<&'r [u8]> {
+        self.0
+            .addresses()
+            .get(0)
+            .map(Entry::from_reader)
+            .filter(|entry| entry.key == b"ckb")
+            .map(|entry| entry.value)
+    }
+
+    /// Whether a section has more than `MAX_UNSORTED_ENTRIES` entries, so its keys must be
+    /// sorted for `duplicate_key` to find every duplicate
+    pub fn needs_sorted_keys(&self) -> bool {
+        self.sections()
+            .iter()
+            .any(|(_, entries)| entries.objs.len() > MAX_UNSORTED_ENTRIES)
+    }
+
+    /// Section and key of an entry sharing its key with an earlier entry of the same section,
+    /// like `KuaiMvpView::duplicate_key`. Keys are compared pairwise instead of sorted into
+    /// a new vector, up to `MAX_UNSORTED_ENTRIES` entries. Larger sections are only compared
+    /// with the previous entry, which finds every duplicate once `keys_sorted` holds
+    pub fn duplicate_key(&self) -> Option<(&'static str, &'r [u8])> {
+        for (name, entries) in self.sections() {
+            let objs = entries.objs;
+            let pairwise = objs.len() <= MAX_UNSORTED_ENTRIES;
+            for index in 1..objs.len() {
+                let key = objs.get_unchecked(index).key().raw_data();
+                let duplicated = if pairwise {
+                    Entries::new(objs).take(index).any(|entry| entry.key == key)
+                } else {
+                    objs.get_unchecked(index - 1).key().raw_data() == key
+                };
+                if duplicated {
+                    return Some((name, key));
+                }
+            }
+        }
+        None
+    }
+
+    /// Same as `KuaiMvpView::keys_sorted`, in a single pass over every section
+    pub fn keys_sorted(&self) -> bool {
+        let has_ckb_address = self.ckb_address().is_some();
+        self.sections().into_iter().all(|(name, mut entries)| {
+            if has_ckb_address && name == "addresses" {
+                entries.next();
+            }
+
+            let mut previous: Option<&[u8]> = None;
+            for entry in entries {
+                if has_ckb_address && name == "addresses" && entry.key == b"ckb" {
+                    return false;
+                }
+                if matches!(previous, Some(previous) if previous >= entry.key) {
+                    return false;
+                }
+                previous = Some(entry.key);
+            }
+            true
+        })
+    }
+
+    /// Same as `KuaiMvpView::verify_addresses`
+    pub fn verify_addresses(&self) -> Result<(), ChainAddressError> {
+        for (_, entries) in self.sections() {
+            for (key, value) in entries.filter_map(|entry| entry.as_strs()) {
+                address::verify_entry(key, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as `KuaiMvpView::verify_dweb`
+    pub fn verify_dweb(&self) -> Result<(), DwebError> {
+        for (key, value) in Entries::new(self.0.dweb()).filter_map(|entry| entry.as_strs()) {
+            dweb::verify_entry(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `KuaiMvpView::verify_labels`
+    pub fn verify_labels(&self) -> Result<(), LabelError> {
+        for (name, entries) in self.sections() {
+            for entry in entries {
+                label::verify_entry(name, entry.label)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the record into a view, for the rules comparing two records
+    pub fn to_view(&self) -> KuaiMvpView {
+        let to_data = |entry: Entry| Data {
+            key: String::from_utf8_lossy(entry.key).into_owned(),
+            value: String::from_utf8_lossy(entry.value).into_owned(),
+            label: String::from_utf8_lossy(entry.label).into_owned(),
+        };
+        let [profile, addresses, custom, dweb] = self.sections();
+
+        KuaiMvpView {
+            profile: profile.1.map(to_data).collect::<Vec<Data>>(),
+            addresses: addresses.1.map(to_data).collect(),
+            custom: custom.1.map(to_data).collect(),
+            dweb: dweb.1.map(to_data).collect(),
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! Records read from the cell data in windows of a fixed size, for records too large to be
+//! loaded at once.
+//!
+//! `RecordStream` checks a JSON record with `json_stream::Validator` and a molecule record
+//! against the layout of `KuaiMvpDataReader::verify`, then holds each entry to the rules of the
+//! deployment as it is read. Fields are compared and decoded from the cell data with a fixed
+//! amount of state, the first error is returned at once and only the view asked for by the
+//! caller is allocated.
+
+use crate::address::{self, ChainAddressError};
+use crate::cell_data::DataFormat;
+use crate::config::{
+    Config, LimitError, FLAG_CANONICAL_JSON, FLAG_SORTED_KEYS, FLAG_STRICT_MOLECULE,
+};
+use crate::dweb::{self, DwebError};
+use crate::generated::mvp_data::{KuaiMvpDataReader, ObjReader};
+use crate::json_record::{JsonRecordError, Section, Unescape};
+use crate::json_stream::{EntrySpans, Span, Utf8, Validator};
+use crate::kuai_mvp_data::{Data, KuaiMvpView};
+use crate::label::{self, LabelError};
+use crate::record_reader::{Field, MoleculeError, MAX_UNSORTED_ENTRIES};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::{self, Ordering};
+use core::convert::Infallible;
+use core::str;
+use molecule::{unpack_number, NUMBER_SIZE};
+
+/// Size of the windows the record is read in
+pub const WINDOW_SIZE: usize = 1024;
+
+/// Longest value read for a check of its format, longer ones are `LimitError::ValueTooLong`
+pub const VALUE_CAPACITY: usize = 256;
+
+/// Long enough for every key with a format and every label, longer ones match none of them
+const NAME_CAPACITY: usize = 16;
+
+/// Size of the loads of a field outside of the window
+const FIELD_CHUNK: usize = 64;
+
+const INVALID_UTF8: JsonRecordError = JsonRecordError::InvalidUtf8;
+
+/// Cell data a record is read from
+pub trait Loader {
+    type Error;
+
+    /// Fill `buf` with the record from `offset`, the stream never reads past its length
+    fn load(&self, buf: &mut [u8], offset: usize) -> Result<(), Self::Error>;
+}
+
+/// Records already in memory
+impl Loader for &[u8] {
+    type Error = Infallible;
+
+    fn load(&self, buf: &mut [u8], offset: usize) -> Result<(), Infallible> {
+        buf.copy_from_slice(&self[offset..offset + buf.len()]);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamError<E> {
+    /// The loader failed
+    Load(E),
+    Json(JsonRecordError),
+    NonCanonicalJson,
+    Molecule(MoleculeError),
+    Limit(LimitError),
+    DuplicateKey,
+    UnsortedKeys,
+    Address(ChainAddressError),
+    Dweb(DwebError),
+    Label(LabelError),
+}
+
+impl<E> From<JsonRecordError> for StreamError<E> {
+    fn from(err: JsonRecordError) -> Self {
+        StreamError::Json(err)
+    }
+}
+
+impl<E> From<MoleculeError> for StreamError<E> {
+    fn from(err: MoleculeError) -> Self {
+        StreamError::Molecule(err)
+    }
+}
+
+impl<E> From<LimitError> for StreamError<E> {
+    fn from(err: LimitError) -> Self {
+        StreamError::Limit(err)
+    }
+}
+
+impl<E> From<ChainAddressError> for StreamError<E> {
+    fn from(err: ChainAddressError) -> Self {
+        StreamError::Address(err)
+    }
+}
+
+impl<E> From<DwebError> for StreamError<E> {
+    fn from(err: DwebError) -> Self {
+        StreamError::Dweb(err)
+    }
+}
+
+impl<E> From<LabelError> for StreamError<E> {
+    fn from(err: LabelError) -> Self {
+        StreamError::Label(err)
+    }
+}
+
+/// Value of the leading "ckb" entry of `addresses`, kept without allocation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CkbAddress {
+    bytes: [u8; VALUE_CAPACITY],
+    len: usize,
+}
+
+impl CkbAddress {
+    /// `None` for addresses longer than `VALUE_CAPACITY`
+    pub fn new(address: &str) -> Option<Self> {
+        let mut bytes = [0u8; VALUE_CAPACITY];
+        bytes.get_mut(..address.len())?.copy_from_slice(address.as_bytes());
+        Some(CkbAddress {
+            bytes,
+            len: address.len(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // copied from a `str` by `new`
+        str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+/// Bytes of the record from `offset` to `end`, from the window when it holds them
+struct CellBytes<'s, L: Loader> {
+    stream: &'s RecordStream<L>,
+    offset: usize,
+    end: usize,
+    chunk: [u8; FIELD_CHUNK],
+    chunk_start: usize,
+    chunk_len: usize,
+}
+
+impl<'s, L: Loader> Iterator for CellBytes<'s, L> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.offset >= self.end {
+            return None;
+        }
+
+        let stream = self.stream;
+        let window = stream.window_start..stream.window_start + stream.window_len;
+        let b = if window.contains(&self.offset) {
+            stream.window[self.offset - stream.window_start]
+        } else {
+            if !(self.chunk_start..self.chunk_start + self.chunk_len).contains(&self.offset) {
+                let len = cmp::min(FIELD_CHUNK, self.end - self.offset);
+                if let Err(err) = stream.loader.load(&mut self.chunk[..len], self.offset) {
+                    stream.load_error.set(Some(err));
+                    self.offset = self.end;
+                    return None;
+                }
+                self.chunk_start = self.offset;
+                self.chunk_len = len;
+            }
+            self.chunk[self.offset - self.chunk_start]
+        };
+        self.offset += 1;
+        Some(b)
+    }
+}
+
+pub struct RecordStream<L: Loader> {
+    loader: L,
+    len: usize,
+    window: [u8; WINDOW_SIZE],
+    window_start: usize,
+    window_len: usize,
+    /// Error of a load made while reading a field, taken by `check_load`
+    load_error: Cell<Option<L::Error>>,
+    /// Entries read in each section
+    counts: [usize; 4],
+    /// Keys of the section being read, up to `MAX_UNSORTED_ENTRIES`, and the last one in order
+    keys: [Span; MAX_UNSORTED_ENTRIES],
+    previous: Option<Span>,
+    /// Whether the keys of the section being read are strictly ascending, with the leading
+    /// "ckb" entry of `addresses` left out as in `KuaiMvpView::keys_sorted`
+    ascending: bool,
+    ckb_first: bool,
+    ckb_address: Option<CkbAddress>,
+}
+
+impl<L: Loader> RecordStream<L> {
+    /// `len` is the length of the record behind its prefix and format tag
+    pub fn new(loader: L, len: usize) -> Self {
+        RecordStream {
+            loader,
+            len,
+            window: [0; WINDOW_SIZE],
+            window_start: 0,
+            window_len: 0,
+            load_error: Cell::new(None),
+            counts: [0; 4],
+            keys: [Span::default(); MAX_UNSORTED_ENTRIES],
+            previous: None,
+            ascending: true,
+            ckb_first: false,
+            ckb_address: None,
+        }
+    }
+
+    /// Value of the leading "ckb" entry of `addresses`, kept when the record is read with rules
+    pub fn ckb_address(&self) -> Option<CkbAddress> {
+        self.ckb_address
+    }
+
+    /// Read the record, copying its entries into `view` when one is given. Without `rules` the
+    /// record is only read, as records already on chain are: unknown molecule fields are
+    /// accepted and no rule of a deployment applies
+    pub fn read(
+        &mut self,
+        format: DataFormat,
+        rules: Option<&Config>,
+        view: Option<&mut KuaiMvpView>,
+    ) -> Result<(), StreamError<L::Error>> {
+        match format {
+            DataFormat::Json => self.read_json(rules, view),
+            DataFormat::Molecule => self.read_molecule(rules, view),
+        }
+    }
+
+    /// The record is fed to the validator a window at a time. A deployment requiring canonical
+    /// JSON reports a malformed record as non-canonical, invalid UTF-8 excepted
+    pub fn read_json(
+        &mut self,
+        rules: Option<&Config>,
+        mut view: Option<&mut KuaiMvpView>,
+    ) -> Result<(), StreamError<L::Error>> {
+        let canonical = rules.is_some_and(|config| config.has_flag(FLAG_CANONICAL_JSON));
+        let mut validator = Validator::new();
+
+        let mut offset = 0;
+        while offset < self.len {
+            self.fill_window(offset)?;
+            let mut read = 0;
+            while read < self.window_len {
+                let (size, entry) = validator.feed_entry(&self.window[read..self.window_len]);
+                read += size;
+                if canonical && !validator.is_canonical() {
+                    return Err(StreamError::NonCanonicalJson);
+                }
+                if let Some((section, entry)) = entry {
+                    self.read_entry(rules, section, entry, view.as_deref_mut())?;
+                }
+            }
+            offset += self.window_len;
+        }
+
+        match validator.finish() {
+            Err(INVALID_UTF8) => Err(INVALID_UTF8.into()),
+            Err(_) if canonical => Err(StreamError::NonCanonicalJson),
+            Err(err) => Err(err.into()),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// The whole layout is checked before any entry is read, so the errors are the ones of
+    /// `RecordReader::verified`
+    pub fn read_molecule(
+        &mut self,
+        rules: Option<&Config>,
+        mut view: Option<&mut KuaiMvpView>,
+    ) -> Result<(), StreamError<L::Error>> {
+        let compatible = !rules.is_some_and(|config| config.has_flag(FLAG_STRICT_MOLECULE));
+        let count = self.check_layout(compatible)?;
+
+        for (section_index, section) in Section::ALL.into_iter().enumerate() {
+            let (start, end) = self.item(0, self.len, section_index, count)?;
+            let len = self.checked_count(start, end)?;
+            for index in 0..len {
+                let (obj_start, obj_end) = self.item(start, end, index, len)?;
+                let field_count = self.checked_count(obj_start, obj_end)?;
+                let mut spans = [Span::default(); 3];
+                for (field_index, field) in Field::ALL.into_iter().enumerate() {
+                    let (start, end) = self.item(obj_start, obj_end, field_index, field_count)?;
+                    let span = Span {
+                        start: start + NUMBER_SIZE,
+                        end,
+                        len: end - start - NUMBER_SIZE,
+                        escaped: false,
+                    };
+                    if !self.is_utf8(span)? {
+                        let err = MoleculeError::InvalidUtf8 {
+                            section,
+                            index,
+                            field,
+                        };
+                        return Err(err.into());
+                    }
+                    spans[field_index] = span;
+                }
+
+                let [key, value, label] = spans;
+                let entry = EntrySpans {
+                    key,
+                    value,
+                    label: Some(label),
+                };
+                self.read_entry(rules, section, entry, view.as_deref_mut())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_entry(
+        &mut self,
+        rules: Option<&Config>,
+        section: Section,
+        entry: EntrySpans,
+        view: Option<&mut KuaiMvpView>,
+    ) -> Result<(), StreamError<L::Error>> {
+        let index = self.counts[section as usize];
+        self.counts[section as usize] += 1;
+
+        if let Some(config) = rules {
+            config.check_entry(section as usize, index, entry.key.len, entry.value.len)?;
+            self.check_key(config.has_flag(FLAG_SORTED_KEYS), section, index, entry.key)?;
+            self.check_value(section, index, &entry)?;
+            let mut label = [0u8; NAME_CAPACITY];
+            match self.read_field(entry.label.unwrap_or_default(), &mut label)? {
+                Some(label) => label::verify_entry(section.name(), label)?,
+                None => return Err(LabelError::UnknownLabel.into()),
+            };
+        }
+
+        if let Some(view) = view {
+            let data = Data {
+                key: self.read_string(entry.key)?,
+                value: self.read_string(entry.value)?,
+                label: match entry.label {
+                    Some(label) => self.read_string(label)?,
+                    None => String::new(),
+                },
+            };
+            view.push(section, data);
+        }
+
+        Ok(())
+    }
+
+    /// Keys are compared pairwise up to `MAX_UNSORTED_ENTRIES` entries, like
+    /// `RecordReader::duplicate_key`. Larger sections, and every section with `sorted`, must
+    /// be in the order of `KuaiMvpView::keys_sorted`
+    fn check_key(
+        &mut self,
+        sorted: bool,
+        section: Section,
+        index: usize,
+        key: Span,
+    ) -> Result<(), StreamError<L::Error>> {
+        if index == 0 {
+            self.ckb_first = section == Section::Addresses && self.field_is(key, b"ckb")?;
+            self.ascending = true;
+            self.previous = None;
+        } else if self.ascending {
+            if self.ckb_first && self.field_is(key, b"ckb")? {
+                self.ascending = false;
+            } else if let Some(previous) = self.previous {
+                self.ascending = self.field_cmp(previous, key)? == Ordering::Less;
+            }
+        }
+        if !(index == 0 && self.ckb_first) {
+            self.previous = Some(key);
+        }
+
+        if sorted || index >= MAX_UNSORTED_ENTRIES {
+            if !self.ascending {
+                return Err(StreamError::UnsortedKeys);
+            }
+            return Ok(());
+        }
+        for other in 0..index {
+            if self.field_eq(self.keys[other], key)? {
+                return Err(StreamError::DuplicateKey);
+            }
+        }
+        self.keys[index] = key;
+
+        Ok(())
+    }
+
+    /// Values of keys with a format of `address` or `dweb`, the leading "ckb" entry is kept
+    /// for `ckb_address`
+    fn check_value(
+        &mut self,
+        section: Section,
+        index: usize,
+        entry: &EntrySpans,
+    ) -> Result<(), StreamError<L::Error>> {
+        let mut key = [0u8; NAME_CAPACITY];
+        let key = match self.read_field(entry.key, &mut key)? {
+            Some(key) => str::from_utf8(key).map_err(|_| INVALID_UTF8)?,
+            None => return Ok(()),
+        };
+        let ckb = section == Section::Addresses && index == 0 && key == "ckb";
+        let dweb = section == Section::Dweb && dweb::has_format(key);
+        if !ckb && !dweb && !address::has_format(key) {
+            return Ok(());
+        }
+
+        let mut value = [0u8; VALUE_CAPACITY];
+        let value = self
+            .read_field(entry.value, &mut value)?
+            .ok_or(LimitError::ValueTooLong)?;
+        let value = str::from_utf8(value).map_err(|_| INVALID_UTF8)?;
+        address::verify_entry(key, value)?;
+        if dweb {
+            dweb::verify_entry(key, value)?;
+        }
+        if ckb {
+            self.ckb_address = CkbAddress::new(value);
+        }
+
+        Ok(())
+    }
+
+    /// The checks of `KuaiMvpDataReader::verify`, returns the number of fields of the record
+    fn check_layout(&mut self, compatible: bool) -> Result<usize, StreamError<L::Error>> {
+        let fits = |count: usize, field_count: usize| {
+            count == field_count || (compatible && count > field_count)
+        };
+
+        let count = match self.item_count(0, self.len)? {
+            Some(count) if fits(count, KuaiMvpDataReader::FIELD_COUNT) => count,
+            _ => return Err(MoleculeError::InvalidRecord.into()),
+        };
+        for (section_index, section) in Section::ALL.into_iter().enumerate() {
+            let (start, end) = self.item(0, self.len, section_index, count)?;
+            let len = match self.item_count(start, end)? {
+                Some(len) => len,
+                None => return Err(MoleculeError::InvalidSection(section).into()),
+            };
+            for index in 0..len {
+                let (obj_start, obj_end) = self.item(start, end, index, len)?;
+                let field_count = match self.item_count(obj_start, obj_end)? {
+                    Some(field_count) if fits(field_count, ObjReader::FIELD_COUNT) => field_count,
+                    _ => return Err(MoleculeError::InvalidEntry { section, index }.into()),
+                };
+                for (field_index, field) in Field::ALL.into_iter().enumerate() {
+                    let (start, end) = self.item(obj_start, obj_end, field_index, field_count)?;
+                    if end - start < NUMBER_SIZE || self.number(start)? != end - start - NUMBER_SIZE
+                    {
+                        let err = MoleculeError::InvalidField {
+                            section,
+                            index,
+                            field,
+                        };
+                        return Err(err.into());
+                    }
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Number of items of the table or dynvec from `start` to `end`, `None` when its header is
+    /// broken
+    fn item_count(
+        &mut self,
+        start: usize,
+        end: usize,
+    ) -> Result<Option<usize>, StreamError<L::Error>> {
+        let len = end - start;
+        if len < NUMBER_SIZE || self.number(start)? != len {
+            return Ok(None);
+        }
+        if len == NUMBER_SIZE {
+            return Ok(Some(0));
+        }
+        if len < NUMBER_SIZE * 2 {
+            return Ok(None);
+        }
+        let offset_first = self.number(start + NUMBER_SIZE)?;
+        if offset_first % NUMBER_SIZE != 0 || offset_first < NUMBER_SIZE * 2 || offset_first > len
+        {
+            return Ok(None);
+        }
+
+        let count = offset_first / NUMBER_SIZE - 1;
+        let mut previous = offset_first;
+        for index in 2..=count {
+            let offset = self.number(start + index * NUMBER_SIZE)?;
+            if offset < previous || offset > len {
+                return Ok(None);
+            }
+            previous = offset;
+        }
+        Ok(Some(count))
+    }
+
+    /// Same as `item_count` for a header already checked by `check_layout`
+    fn checked_count(&mut self, start: usize, end: usize) -> Result<usize, StreamError<L::Error>> {
+        Ok(self.item_count(start, end)?.unwrap_or_default())
+    }
+
+    /// Bounds of item `index` of a table or dynvec whose header passed `item_count`
+    fn item(
+        &mut self,
+        start: usize,
+        end: usize,
+        index: usize,
+        count: usize,
+    ) -> Result<(usize, usize), StreamError<L::Error>> {
+        let mut offset = |index: usize| {
+            if index == count {
+                return Ok(end - start);
+            }
+            self.number(start + (index + 1) * NUMBER_SIZE)
+        };
+        Ok((start + offset(index)?, start + offset(index + 1)?))
+    }
+
+    /// Molecule number at `offset`, the window is moved there when it doesn't hold it
+    fn number(&mut self, offset: usize) -> Result<usize, StreamError<L::Error>> {
+        if offset < self.window_start || offset + NUMBER_SIZE > self.window_start + self.window_len
+        {
+            self.fill_window(offset)?;
+        }
+        let start = offset - self.window_start;
+        Ok(unpack_number(&self.window[start..start + NUMBER_SIZE]) as usize)
+    }
+
+    fn fill_window(&mut self, offset: usize) -> Result<(), StreamError<L::Error>> {
+        let len = cmp::min(WINDOW_SIZE, self.len - offset);
+        // the window is invalid until loaded
+        self.window_len = 0;
+        self.loader
+            .load(&mut self.window[..len], offset)
+            .map_err(StreamError::Load)?;
+        self.window_start = offset;
+        self.window_len = len;
+        Ok(())
+    }
+
+    /// Decoded bytes of a field
+    fn field(&self, span: Span) -> Unescape<CellBytes<'_, L>> {
+        let bytes = CellBytes {
+            stream: self,
+            offset: span.start,
+            end: span.end,
+            chunk: [0; FIELD_CHUNK],
+            chunk_start: 0,
+            chunk_len: 0,
+        };
+        Unescape::new(bytes, span.escaped)
+    }
+
+    fn check_load(&self) -> Result<(), StreamError<L::Error>> {
+        match self.load_error.take() {
+            Some(err) => Err(StreamError::Load(err)),
+            None => Ok(()),
+        }
+    }
+
+    fn is_utf8(&self, span: Span) -> Result<bool, StreamError<L::Error>> {
+        let mut utf8 = Utf8::new();
+        self.field(span).for_each(|b| utf8.push(b));
+        self.check_load()?;
+        Ok(utf8.is_complete())
+    }
+
+    fn field_is(&self, span: Span, s: &[u8]) -> Result<bool, StreamError<L::Error>> {
+        let eq = span.len == s.len() && self.field(span).eq(s.iter().copied());
+        self.check_load()?;
+        Ok(eq)
+    }
+
+    fn field_eq(&self, a: Span, b: Span) -> Result<bool, StreamError<L::Error>> {
+        let eq = a.len == b.len && self.field(a).eq(self.field(b));
+        self.check_load()?;
+        Ok(eq)
+    }
+
+    fn field_cmp(&self, a: Span, b: Span) -> Result<Ordering, StreamError<L::Error>> {
+        let ordering = self.field(a).cmp(self.field(b));
+        self.check_load()?;
+        Ok(ordering)
+    }
+
+    /// Decoded field in `buf`, `None` when it is longer
+    fn read_field<'b>(
+        &self,
+        span: Span,
+        buf: &'b mut [u8],
+    ) -> Result<Option<&'b [u8]>, StreamError<L::Error>> {
+        if span.len > buf.len() {
+            return Ok(None);
+        }
+        let mut len = 0;
+        for (slot, b) in buf.iter_mut().zip(self.field(span)) {
+            *slot = b;
+            len += 1;
+        }
+        self.check_load()?;
+        Ok(Some(&buf[..len]))
+    }
+
+    fn read_string(&self, span: Span) -> Result<String, StreamError<L::Error>> {
+        let mut bytes = Vec::with_capacity(span.len);
+        bytes.extend(self.field(span));
+        self.check_load()?;
+        String::from_utf8(bytes).map_err(|_| INVALID_UTF8.into())
+    }
+}
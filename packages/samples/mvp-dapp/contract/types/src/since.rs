@@ -0,0 +1,35 @@
+//! Comparison of input `since` values, see RFC 0017 "Transaction valid since".
+
+const FLAGS_MASK: u64 = 0xff00_0000_0000_0000;
+const VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+const METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+const METRIC_EPOCH: u64 = 0x2000_0000_0000_0000;
+
+/// Epoch with fraction as `(number * length + index, length)`
+fn epoch_fraction(value: u64) -> (u128, u128) {
+    let number = (value & 0xff_ffff) as u128;
+    let index = ((value >> 24) & 0xffff) as u128;
+    let length = ((value >> 40) & 0xffff) as u128;
+    if length == 0 {
+        return (number, 1);
+    }
+    (number * length + index, length)
+}
+
+/// Whether an input declaring `since` is valid no earlier than `required`.
+///
+/// Both values must use the same flags, the chain checks the `since` of the input itself.
+pub fn reaches(since: u64, required: u64) -> bool {
+    if since & FLAGS_MASK != required & FLAGS_MASK {
+        return false;
+    }
+
+    let (value, required_value) = (since & VALUE_MASK, required & VALUE_MASK);
+    if required & METRIC_MASK == METRIC_EPOCH {
+        let (a, a_length) = epoch_fraction(value);
+        let (b, b_length) = epoch_fraction(required_value);
+        return a * b_length >= b * a_length;
+    }
+
+    value >= required_value
+}
@@ -0,0 +1,143 @@
+//! Codecs of the kuai stores, mirrors of `ChainStorage`, `JSONStorage` and `MoleculeStorage` in
+//! `packages/models/src/store`, and of the `{ offset, length }` options locating a field in
+//! the cell data.
+
+use crate::cell_data::DAPP_DATA_PREFIX_LEN;
+use crate::kuai_mvp_data::{KuaiMvpView, ViewError};
+use crate::record_reader::MoleculeError;
+use alloc::vec::Vec;
+use core::cmp;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub trait ChainStorage<T> {
+    type Error;
+
+    fn serialize(&self, data: &T) -> Result<Vec<u8>, Self::Error>;
+
+    fn deserialize(&self, data: &[u8]) -> Result<T, Self::Error>;
+
+    fn clone_data(&self, data: &T) -> Result<T, Self::Error> {
+        self.deserialize(&self.serialize(data)?)
+    }
+}
+
+/// `JSON.stringify` and `JSON.parse`. Both write strings with the same escapes and members in
+/// the order of the fields, so a `KuaiMvpView` gets the bytes of the `StoreType['data']`
+/// holding the same sections
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonStorage;
+
+impl<T: Serialize + DeserializeOwned> ChainStorage<T> for JsonStorage {
+    type Error = ViewError;
+
+    fn serialize(&self, data: &T) -> Result<Vec<u8>, ViewError> {
+        serde_json::to_vec(data).map_err(|_| ViewError::InvalidJson)
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<T, ViewError> {
+        serde_json::from_slice(data).map_err(|_| ViewError::InvalidJson)
+    }
+}
+
+/// The codec `MoleculeStorage` builds for the `KuaiMvpData` schema: a table of `profile`,
+/// `addresses`, `custom` and `dweb`, each a vector of `key`, `value` and `label` tables of
+/// strings. Fields unknown to this crate are only read when `compatible` is set. The codec
+/// writes no format tag, record cells keep it before `MOLECULE_RECORD_SCHEMA`
+#[derive(Clone, Copy, Debug)]
+pub struct MoleculeStorage {
+    pub compatible: bool,
+}
+
+impl Default for MoleculeStorage {
+    fn default() -> Self {
+        MoleculeStorage { compatible: true }
+    }
+}
+
+impl ChainStorage<KuaiMvpView> for MoleculeStorage {
+    type Error = MoleculeError;
+
+    fn serialize(&self, data: &KuaiMvpView) -> Result<Vec<u8>, MoleculeError> {
+        let bytes = data
+            .as_molecule_data()
+            .map_err(|_| MoleculeError::InvalidRecord)?;
+        Ok(bytes.to_vec())
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<KuaiMvpView, MoleculeError> {
+        KuaiMvpView::from_molecule_verified(data, self.compatible)
+    }
+}
+
+/// Where a store keeps its value in the cell data, `length` 0 runs to the end. Offsets past
+/// the data are clamped like the `slice` of the hex strings in `Store`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Schema of `RecordModel`, the record follows the `mvp-dapp` prefix. JSON records are
+/// written without a format tag
+pub const RECORD_SCHEMA: Schema = Schema {
+    offset: DAPP_DATA_PREFIX_LEN,
+    length: 0,
+};
+
+/// Schema of the molecule records, which follow the prefix and the `FORMAT_MOLECULE` tag
+pub const MOLECULE_RECORD_SCHEMA: Schema = Schema {
+    offset: DAPP_DATA_PREFIX_LEN + 1,
+    length: 0,
+};
+
+impl Schema {
+    fn end(&self, len: usize) -> usize {
+        match self.length {
+            0 => len,
+            length => cmp::min(self.offset + length, len),
+        }
+    }
+
+    /// The bytes `Store` deserializes
+    pub fn read<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        let start = cmp::min(self.offset, data.len());
+        &data[start..self.end(data.len())]
+    }
+
+    /// Same as `Store::initOnChain`, the bytes before `offset` are zeros for the caller to fill
+    pub fn init(&self, value: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.offset + value.len());
+        data.resize(self.offset, 0);
+        data.extend_from_slice(value);
+        data
+    }
+
+    /// Same as `Store::set`, the bytes of `original` around the field are kept
+    pub fn write(&self, original: &[u8], value: &[u8]) -> Vec<u8> {
+        let start = cmp::min(self.offset, original.len());
+        let mut data = Vec::with_capacity(original.len() + value.len());
+        data.extend_from_slice(&original[..start]);
+        data.extend_from_slice(value);
+        if self.length != 0 {
+            data.extend_from_slice(&original[self.end(original.len())..]);
+        }
+        data
+    }
+
+    pub fn deserialize<T, S: ChainStorage<T>>(
+        &self,
+        storage: &S,
+        data: &[u8],
+    ) -> Result<T, S::Error> {
+        storage.deserialize(self.read(data))
+    }
+
+    pub fn serialize<T, S: ChainStorage<T>>(
+        &self,
+        storage: &S,
+        original: &[u8],
+        value: &T,
+    ) -> Result<Vec<u8>, S::Error> {
+        Ok(self.write(original, &storage.serialize(value)?))
+    }
+}
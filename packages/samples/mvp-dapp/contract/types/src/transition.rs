@@ -0,0 +1,103 @@
+//! Rules applied when a record cell is created, updated or destroyed.
+
+use crate::config::{Config, FLAG_IMMUTABLE};
+use crate::diff::Change;
+use crate::kuai_mvp_data::KuaiMvpView;
+use crate::label::{LABEL_REQUIRED, LABEL_VERIFIED};
+use crate::operation::Operation;
+use crate::since;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// Record outputs without record inputs
+    Create,
+    /// Record inputs paired with record outputs
+    Update,
+    /// Record inputs without record outputs
+    Destroy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionError {
+    MissingCkbAddress,
+    /// A `required` entry missing from the new record or no longer labeled `required`
+    RequiredEntryRemoved,
+    CkbAddressChanged,
+    ImmutableRecord,
+    LockInPeriod,
+    /// A `verified` entry keeping its label with another value
+    VerifiedValueChanged,
+}
+
+/// `ckb_address` is the value of the leading "ckb" entry of the new record, which is only read
+/// as a stream. Duplicate keys are left to the stream, which knows whether the sections are sorted
+pub fn verify_create(ckb_address: Option<&str>) -> Result<(), TransitionError> {
+    if ckb_address.is_none() {
+        return Err(TransitionError::MissingCkbAddress);
+    }
+
+    Ok(())
+}
+
+pub fn verify_update(
+    old: &KuaiMvpView,
+    new: &KuaiMvpView,
+    operation: Operation,
+) -> Result<(), TransitionError> {
+    if new.ckb_address().is_none() {
+        return Err(TransitionError::MissingCkbAddress);
+    }
+
+    // a re-keyed entry is a removed one
+    let changes = KuaiMvpView::diff(old, new);
+    for change in changes.iter() {
+        match change {
+            Change::Removed { entry, .. } if entry.label == LABEL_REQUIRED => {
+                return Err(TransitionError::RequiredEntryRemoved);
+            }
+            Change::LabelChanged { old: label, .. } if label == LABEL_REQUIRED => {
+                return Err(TransitionError::RequiredEntryRemoved);
+            }
+            Change::ValueChanged { key, .. } => {
+                let label_changed = changes.iter().any(|other| {
+                    matches!(other, Change::LabelChanged { .. })
+                        && other.section() == change.section()
+                        && other.key() == key
+                });
+                if !label_changed && entry_label(old, change) == Some(LABEL_VERIFIED) {
+                    return Err(TransitionError::VerifiedValueChanged);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if operation != Operation::Transfer && old.ckb_address() != new.ckb_address() {
+        return Err(TransitionError::CkbAddressChanged);
+    }
+
+    Ok(())
+}
+
+/// Label of the entry of `view` a change applies to
+fn entry_label<'a>(view: &'a KuaiMvpView, change: &Change) -> Option<&'a str> {
+    let (_, items) = view.sections()[change.section() as usize];
+    items
+        .iter()
+        .find(|item| item.key == change.key())
+        .map(|item| item.label.as_str())
+}
+
+/// `since` is the since of the record input. The contract also checks what is left in the
+/// outputs
+pub fn verify_destroy(config: &Config, since: u64) -> Result<(), TransitionError> {
+    if config.has_flag(FLAG_IMMUTABLE) {
+        return Err(TransitionError::ImmutableRecord);
+    }
+
+    if config.lock_in_period != 0 && !since::reaches(since, config.lock_in_period) {
+        return Err(TransitionError::LockInPeriod);
+    }
+
+    Ok(())
+}
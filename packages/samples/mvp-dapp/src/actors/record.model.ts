@@ -38,6 +38,21 @@ export type StoreType = {
   }
 }
 
+/**
+ * The record with its sections and the fields of its entries ordered by name and without empty sections, so
+ * `JSON.stringify` writes the canonical JSON of `types::canonical_json` in the contract
+ */
+export const canonicalRecord = (data: StoreType['data']): StoreType['data'] => {
+  const record: StoreType['data'] = {}
+  for (const name of ['addresses', 'custom', 'dweb', 'profile'] as const) {
+    const items = data[name]
+    if (items?.length) {
+      record[name] = items.map(({ key, label, value }) => ({ key, label: label ?? '', value }))
+    }
+  }
+  return record
+}
+
 /**
  * add business logic in an actor
  */
@@ -73,7 +88,7 @@ export class RecordModel extends JSONStore<{ data: { offset: number; schema: Sto
   update(newValue: StoreType['data']) {
     const inputs = Object.values(this.chainData)
     if (!inputs.length) throw new InternalServerError('No mvp cell to set value')
-    const { data } = this.initOnChain({ data: newValue })
+    const { data } = this.initOnChain({ data: canonicalRecord(newValue) })
     const outputCapacity = inputs
       .reduce((pre: BI, cur) => pre.add(cur.cell.cellOutput.capacity), BI.from(0))
       .sub(TX_FEE)